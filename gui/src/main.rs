@@ -8,7 +8,6 @@ use egui::plot::Plot;
 mod ui;
 use ui::*;
 
-use srs_to_dfa::util::Ruleset;
 
 #[cfg(target_arch = "wasm32")]
 pub use web_time::Instant;
@@ -82,7 +81,7 @@ impl eframe::App for MyApp {
         egui::TopBottomPanel::top("my_panel").show(ctx, |ui| {
             ui.add_enabled_ui(!self.e_reporter.error_onscreen, |ui|{
             ui.horizontal(|ui|{
-                self.prep_panel.topbar_update(ui);
+                self.prep_panel.topbar_update(ui, &self.dfa_constructor);
                 if ui.button("Generate DFA").clicked() {
                     self.open_generator_window = !self.open_generator_window;
                 }
@@ -102,8 +101,10 @@ impl eframe::App for MyApp {
             ui.add_enabled_ui((!self.dfa_constructor.has_started && !self.dfa_constructor.has_finished) || self.dfa_constructor.has_finished, |ui|{
             ui.horizontal_wrapped(|ui|{
             if self.prep_panel.solve_window_update(ui) { 
-                self.dfa_constructor.run_dfa(self.prep_panel.solver_type,Ruleset::from_string(&self.prep_panel.srs_text),self.prep_panel.goal.clone(),self.prep_panel.sig_k,self.prep_panel.verify_run);
-                Plot::new("my_plot").reset();
+                if let Some(rules) = self.prep_panel.parse_ruleset() {
+                    self.dfa_constructor.run_dfa(self.prep_panel.solver_type,rules,self.prep_panel.goal.clone(),self.prep_panel.sig_k,self.prep_panel.verify_run);
+                    Plot::new("my_plot").reset();
+                }
             }
             });
             self.dfa_constructor.update_solve_window(ui);
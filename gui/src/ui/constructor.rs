@@ -2,7 +2,7 @@ use std::{sync::mpsc::{Sender,Receiver}, thread::JoinHandle, time::Duration};
 
 use egui::{Ui, Color32, RichText};
 
-use srs_to_dfa::{solver::{DFAStructure,MinkidSolver, SSStructure, Solver, SRSSolver, SubsetSolver, BFSSolver, HashSolver}, util::{DFA, Ruleset, SymbolIdx, SymbolSet}};
+use srs_to_dfa::{solver::{DFAStructure,MinkidSolver, SSStructure, Solver, SRSSolver, SubsetSolver, BFSSolver, HashSolver}, util::{DFA, Ruleset, SymbolIdx, SymbolSet}, project::ProjectResult};
 
 use super::{Instant,execute};
 
@@ -10,6 +10,9 @@ use std::path::PathBuf;
 
 use super::{AvailableSolver, PrepPanel, Error};
 
+//Results whose regex would need more symbols than this aren't shown as one
+const MAX_SHOWN_REGEX_SYMBOLS : usize = 60;
+
 
 pub struct DFAConstructor {
     dfa_reciever : Option<Receiver<(DFAStructure,SSStructure)>>,
@@ -17,6 +20,10 @@ pub struct DFAConstructor {
     pub dfa_content : Option<(DFAStructure,SSStructure)>,
     pub last_solver : Option<SolverContents>,
     pub final_dfa : Option<DFA>,
+    //Regex of the final DFA once it's been worked out, None inside if it's too long to be worth showing
+    final_regex : Option<Option<String>>,
+    //Works the regex out off the UI thread, as it can take a while for big DFAs
+    regex_handle : Option<JoinHandle<Option<String>>>,
     pub iteration_state_lens : Vec<usize>,
     handle : Option<JoinHandle<DFA>>,
     solve_string : String,
@@ -29,7 +36,16 @@ pub struct DFAConstructor {
     pub has_started : bool,
     pub has_finished : bool,
     pub initialization_dur : Option<Duration>,
+    //When the current run started, verification reruns included
+    run_start : Instant,
+    //How long the last finished run took
+    run_duration : Option<Duration>,
     verify_run : bool,
+    //Saved DFA (and its file name) that strings are tested against, whatever its outputs are
+    tested_dfa : Option<(String,DFA<String,serde_json::Value>)>,
+    test_string : String,
+    //Output of the tested DFA on the last string tested
+    test_output : Option<String>,
     e_reporter : Sender<Error>
 }
 
@@ -48,6 +64,8 @@ impl DFAConstructor{
             phase_reciever : None,
             dfa_content : None,
             final_dfa : None,
+            final_regex : None,
+            regex_handle : None,
             handle : None,
             phase_content : vec![],
             phase_idx : 0,
@@ -61,12 +79,24 @@ impl DFAConstructor{
             last_solve_string : None,
             verify_run : true,
             initialization_dur : None,
+            run_start : Instant::now(),
+            run_duration : None,
             iteration_state_lens : vec![],
+            tested_dfa : None,
+            test_string : "".to_owned(),
+            test_output : None,
             e_reporter : e_reporter
         }
     }
 
     pub fn update(&mut self, prep_panel : &mut PrepPanel) {
+        if let Some(result) = prep_panel.loaded_result.take() {
+            self.load_result(result, prep_panel);
+        }
+        if let Some(tested) = prep_panel.loaded_tested_dfa.take() {
+            self.tested_dfa = Some(tested);
+            self.test_output = None;
+        }
         if cfg!(not(target_arch = "wasm32")) {
             let mut handle = None;
             std::mem::swap(&mut handle, &mut self.handle);
@@ -82,13 +112,28 @@ impl DFAConstructor{
                             prep_panel.sig_k -= 1;
                         }
                         self.has_finished = true;
+                        self.run_duration = Some(Instant::now() - self.run_start);
                     }
                     self.final_dfa = Some(new_dfa);
+                    self.final_regex = None;
+                    self.regex_handle = None;
     
                 } else {
                     self.handle = Some(h);
                 }                
             }
+            if let Some(h) = self.regex_handle.take() {
+                if h.is_finished() {
+                    self.final_regex = Some(h.join().unwrap());
+                } else {
+                    self.regex_handle = Some(h);
+                }
+            }
+        }
+        if self.has_finished && self.final_regex.is_none() && self.regex_handle.is_none() {
+            if let Some(dfa) = self.final_dfa.clone() {
+                self.regex_arch(dfa);
+            }
         }
         //Phase messages loop
         loop {
@@ -127,9 +172,12 @@ impl DFAConstructor{
                                 if cfg!(target_arch = "wasm32") {
                                     let event = self.dfa_content.as_ref().unwrap();
                                     self.final_dfa = Some(srs_to_dfa::solver::event_to_dfa(&event.0,&event.1, &self.last_solver.as_ref().unwrap().rules));
+                                    self.final_regex = None;
+                                    self.regex_handle = None;
                                 }
                                 self.dfa_reciever = None;
                                 self.has_finished = true;
+                                self.run_duration = Some(Instant::now() - self.run_start);
                             },
                             std::sync::mpsc::TryRecvError::Empty => {
                                 break; 
@@ -143,12 +191,55 @@ impl DFAConstructor{
             }
         }
     }
+    //The finished result, as project files store it
+    pub fn project_result(&self) -> Option<ProjectResult> {
+        if !self.has_finished {
+            return None;
+        }
+        let solver = self.last_solver.as_ref()?;
+        Some(ProjectResult {
+            dfa : self.final_dfa.clone()?,
+            //Verified runs end by confirming k-1 gives the same DFA as k
+            sig_k : if self.verify_run {solver.sig_k - 1} else {solver.sig_k},
+            duration : self.run_duration?
+        })
+    }
+
+    //Shows a result from a project file as if it had just been generated
+    fn load_result(&mut self, result : ProjectResult, prep_panel : &PrepPanel) {
+        if let Some(rules) = prep_panel.parse_ruleset() {
+            self.last_solver = Some(SolverContents { rules, goal : prep_panel.goal.clone(), solve_type : prep_panel.solver_type, sig_k : result.sig_k });
+        }
+        self.handle = None;
+        self.dfa_reciever = None;
+        self.phase_reciever = None;
+        self.dfa_content = None;
+        self.solve_path = None;
+        self.initialization_dur = None;
+        self.iteration_state_lens.clear();
+        self.phase_content = vec![vec![]; prep_panel.solver_type.get_phases().len()];
+        self.max_duration = 0.0;
+        self.verify_run = false;
+        self.run_duration = Some(result.duration);
+        self.final_dfa = Some(result.dfa);
+        self.final_regex = None;
+        self.regex_handle = None;
+        self.has_started = true;
+        self.has_finished = true;
+    }
+
     pub fn update_solve_window(&mut self, ui : &mut Ui) {
     ui.add_enabled_ui(self.final_dfa.is_some(), |ui|{
     ui.separator();
     if ui.button("Save DFA").clicked() {
         save_dfa(self.final_dfa.as_ref().unwrap().clone(),self.e_reporter.clone());
     }
+    if let Some(Some(regex)) = &self.final_regex {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("As a regex:");
+            ui.label(RichText::new(regex.as_str()).monospace());
+        });
+    }
 
     ui.separator();
     ui.horizontal_wrapped(|ui| {
@@ -202,6 +293,29 @@ impl DFAConstructor{
     }*/
     });
 
+    if let Some((name, tested)) = &self.tested_dfa {
+    ui.separator();
+    ui.horizontal_wrapped(|ui| {
+    ui.label(format!("String to test against {}:", name));
+    ui.text_edit_singleline(&mut self.test_string);
+    if ui.button("Test String").clicked() {
+        match tested.symbol_set.string_to_symbols(&self.test_string.split(" ").collect()) {
+            Ok(input_str) => {
+                self.test_output = Some(tested.contains(&input_str).to_string());
+            }
+            Err(idx) => {
+                let _ = self.e_reporter.send(Error { 
+                    title: "Unrecognized symbol".to_string(), 
+                    body: RichText::new(format!("\"{}\" not recognized. Make sure to put a space in between symbols!",self.test_string.split(" ").nth(idx).unwrap())) });
+            }
+        }
+    }
+    });
+    if let Some(output) = &self.test_output {
+        ui.label(format!("Output: {}", output));
+    }
+    }
+
 
     }
 
@@ -212,6 +326,14 @@ impl DFAConstructor{
         self.phase_reciever = Some(phase_rx);
         self.handle = Some(temp_h);
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn regex_arch(&mut self, dfa : DFA) {
+        self.regex_handle = Some(std::thread::spawn(move || dfa.to_regex_within(MAX_SHOWN_REGEX_SYMBOLS)));
+    }
+    #[cfg(target_arch = "wasm32")]
+    fn regex_arch(&mut self, dfa : DFA) {
+        self.final_regex = Some(dfa.to_regex_within(MAX_SHOWN_REGEX_SYMBOLS));
+    }
     #[cfg(target_arch = "wasm32")]
     fn run_dfa_arch<S>(&mut self, solver : S, k : usize) where S : Solver{
         let (dfa_rx, phase_rx) = solver.run_debug(k,vec![]); 
@@ -219,6 +341,10 @@ impl DFAConstructor{
         self.phase_reciever = Some(phase_rx);
     }
     pub fn run_dfa(&mut self, solver : AvailableSolver, rules : Ruleset, goal : DFA, k : usize, verify_run : bool){
+        //Verification reruns happen while the first run is still unfinished
+        if !self.has_started || self.has_finished {
+            self.run_start = Instant::now();
+        }
         
         match solver {
             AvailableSolver::Minkid => {
@@ -274,6 +400,8 @@ impl DFAConstructor{
 
         self.verify_run = verify_run;
         self.final_dfa = None;
+        self.final_regex = None;
+        self.regex_handle = None;
         self.initialization_dur = None;
         self.phase_idx = 0;
         self.iteration_state_lens.clear();
@@ -29,10 +29,13 @@ pub use web_time::Instant;
 pub use std::time::Instant;
 
 use srs_to_dfa::solver::*;
+use srs_to_dfa::project::SolverType;
 
 pub enum OpenItem {
     Goal,
-    SRS
+    SRS,
+    Project,
+    TestedDFA
 }
 
 #[derive(Clone,Copy, PartialEq)]
@@ -55,6 +58,28 @@ impl AvailableSolver {
     }
 }
 
+impl From<AvailableSolver> for SolverType {
+    fn from(solver : AvailableSolver) -> Self {
+        match solver {
+            AvailableSolver::Minkid => SolverType::Minkid,
+            AvailableSolver::Subset => SolverType::Subset,
+            AvailableSolver::BFS => SolverType::BFS,
+            AvailableSolver::Hash => SolverType::Hash
+        }
+    }
+}
+
+impl From<SolverType> for AvailableSolver {
+    fn from(solver : SolverType) -> Self {
+        match solver {
+            SolverType::Minkid => AvailableSolver::Minkid,
+            SolverType::Subset => AvailableSolver::Subset,
+            SolverType::BFS => AvailableSolver::BFS,
+            SolverType::Hash => AvailableSolver::Hash
+        }
+    }
+}
+
 impl Display for AvailableSolver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -71,6 +96,8 @@ fn open_file(target : OpenItem, file_s: Sender<(String,FileHandle,OpenItem)>) {
     let task = match target {
         OpenItem::SRS => rfd::AsyncFileDialog::new().pick_file(),
         OpenItem::Goal => rfd::AsyncFileDialog::new().add_filter("Recognized DFA types", &["dfa","jff"]).pick_file(),
+        OpenItem::Project => rfd::AsyncFileDialog::new().add_filter("SRS projects", &["srsproj"]).pick_file(),
+        OpenItem::TestedDFA => rfd::AsyncFileDialog::new().add_filter("Saved DFAs", &["dfa"]).pick_file(),
     };
     
     let async_f = async move {
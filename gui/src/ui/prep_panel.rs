@@ -1,21 +1,31 @@
 use std::{path::PathBuf, sync::mpsc::Sender};
 
 use egui::{Ui, RichText};
+use rfd::FileHandle;
 
-use srs_to_dfa::{util::{DFA, Ruleset}, builder::*};
+use srs_to_dfa::{util::{DFA, Ruleset}, builder::*, project::{Project, ProjectResult}};
 
-use super::{open_file, OpenItem, PathSender, PathReciever, AvailableSolver, Error};
+use super::{open_file, OpenItem, PathSender, PathReciever, AvailableSolver, Error, DFAConstructor};
 
 pub struct PrepPanel {
     pub srs_text : String,
+    //Directory of the opened SRS file, used to resolve its include directives
+    srs_dir : Option<PathBuf>,
     pub sig_k : usize,
     pub goal : DFA,
     path_r : PathReciever,
     path_s : PathSender,
     ruleset_pick : ExampleRulesets,
     goal_pick : ExampleGoals,
+    //Contents of the regex box in the goal picker
+    goal_regex : String,
     pub verify_run : bool,
     pub solver_type : AvailableSolver,
+    //Result from an opened project file, waiting for the DFA constructor to pick it up
+    pub loaded_result : Option<ProjectResult>,
+    //Saved DFA (and its file name) to test strings against, waiting for the DFA constructor to pick it up.
+    //Its outputs are kept as plain JSON, so results with outputs other than accept/reject open too
+    pub loaded_tested_dfa : Option<(String,DFA<String,serde_json::Value>)>,
     e_reporter : Sender<Error>
 }
 
@@ -117,21 +127,35 @@ impl PrepPanel {
         let channel = std::sync::mpsc::channel();
         Self { 
             srs_text: rules.to_string(), 
+            srs_dir : None,
             sig_k: 5, 
             goal: goal, 
             path_s : channel.0,
             path_r : channel.1,
             ruleset_pick : ExampleRulesets::OneDPeg,
             goal_pick : ExampleGoals::OnlyOne1,
+            goal_regex : "0* 1 0*".to_owned(),
             verify_run : true,
             solver_type : AvailableSolver::Minkid,
+            loaded_result : None,
+            loaded_tested_dfa : None,
             e_reporter : e_reporter
             }
     }
 
-    pub fn topbar_update(&mut self, ui : &mut Ui) {
+    pub fn topbar_update(&mut self, ui : &mut Ui, dfa_constructor : &DFAConstructor) {
         ui.menu_button("File", |ui| {
 
+            if ui.button("Save project").clicked() {
+                self.save_project(dfa_constructor);
+                ui.close_menu();
+            }
+
+            if ui.button("Open project").clicked() {
+                open_file(OpenItem::Project, self.path_s.clone());
+                ui.close_menu();
+            }
+
             if ui.button("Save SRS").clicked() {
                 save_srs(self.srs_text.clone());
                 ui.close_menu();
@@ -142,6 +166,11 @@ impl PrepPanel {
                 ui.close_menu();
             }
 
+            if ui.button("Open DFA to test").clicked() {
+                open_file(OpenItem::TestedDFA, self.path_s.clone());
+                ui.close_menu();
+            }
+
             ui.menu_button("Load example SRS", |ui|{
             for i in vec![ExampleRulesets::OneDPeg,
                                 ExampleRulesets::ThreeRuleOneDPeg,
@@ -154,6 +183,7 @@ impl PrepPanel {
                 if ui.button(i.to_string()).clicked() {
                     self.ruleset_pick = i;
                     self.srs_text = self.ruleset_pick.to_srs().to_string();
+                    self.srs_dir = None;
                     ui.close_menu();
                 }
             }
@@ -200,6 +230,13 @@ impl PrepPanel {
                 ui.close_menu();
                 open_file(OpenItem::Goal, self.path_s.clone());
             }
+            ui.menu_button("Goal from regex", |ui| {
+                ui.text_edit_singleline(&mut self.goal_regex);
+                if ui.button("Use regex").clicked() {
+                    self.load_regex_goal();
+                    ui.close_menu();
+                }
+            });
             ui.menu_button("Load example goal", |ui| {
                 for i in vec![ExampleGoals::All0, ExampleGoals::OnlyOne1, ExampleGoals::OnlyOne2, ExampleGoals::All000,ExampleGoals::OneDPegResultxThree] {
                     if ui.button(i.to_string()).clicked() {
@@ -219,6 +256,54 @@ impl PrepPanel {
         was_solve_asked
     }
 
+    //Parses the SRS editor contents, reporting every malformed line instead of crashing the solver thread
+    pub fn parse_ruleset(&self) -> Option<Ruleset> {
+        match Ruleset::parse_with_includes(&self.srs_text, |include| read_include(&self.srs_dir, include)) {
+            Ok(rules) => Some(rules),
+            Err(errors) => {
+                let body = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n");
+                let _ = self.e_reporter.send(Error {title : "Invalid SRS".to_owned(), body : RichText::new(body)});
+                None
+            }
+        }
+    }
+
+    //Regex symbols are the ones the SRS in the editor uses
+    fn load_regex_goal(&mut self) {
+        if let Some(rules) = self.parse_ruleset() {
+            match DFA::from_regex(&self.goal_regex, &rules.symbol_set) {
+                Ok(goal) => {
+                    self.goal = goal;
+                    self.goal_pick = ExampleGoals::Custom(self.goal_regex.clone());
+                }
+                Err(e) => {let _ = self.e_reporter.send(Error {title : "Invalid regex".to_owned(), body : RichText::new(e.to_string())});}
+            }
+        }
+    }
+
+    //Bundles the current settings, and the last finished result if there is one, into a project file
+    fn save_project(&self, dfa_constructor : &DFAConstructor) {
+        if let Some(rules) = self.parse_ruleset() {
+            let mut project = Project::new(rules, self.goal.clone(), self.sig_k, self.solver_type.into());
+            project.verify = self.verify_run;
+            project.result = dfa_constructor.project_result();
+            save_project_file(project.to_json());
+        }
+    }
+
+    fn load_project(&mut self, project : Project, fh : &FileHandle) {
+        let name = PathBuf::from(fh.file_name()).file_name().unwrap().to_os_string().into_string().unwrap();
+        self.srs_text = project.rules.to_string();
+        self.srs_dir = None;
+        self.ruleset_pick = ExampleRulesets::Custom(name.clone());
+        self.goal = project.goal;
+        self.goal_pick = ExampleGoals::Custom(name);
+        self.sig_k = project.sig_k;
+        self.verify_run = project.verify;
+        self.solver_type = project.solver.into();
+        self.loaded_result = project.result;
+    }
+
     pub fn update(&mut self, ui : &mut Ui) {
         loop {
             match self.path_r.try_recv() {
@@ -226,17 +311,35 @@ impl PrepPanel {
                     match item_t {
                         OpenItem::Goal => {
                             let path = PathBuf::from(fh.file_name());
-                            
-                            match path.extension().unwrap().to_str().unwrap() {
-                                "dfa" => {self.goal = serde_json::from_str(&contents).unwrap();self.goal_pick = ExampleGoals::Custom(path.file_name().unwrap().to_os_string().into_string().unwrap());},
-                                "jff" => {self.goal = DFA::load_jflap_from_string(&contents);self.goal_pick = ExampleGoals::Custom(path.file_name().unwrap().to_os_string().into_string().unwrap());},
-                                _ => {let _ = self.e_reporter.send(Error {title : "Unrecognized file type".to_owned(),body : RichText::new("Only .jff and .dfa files can be parsed")});}
+                            let name = path.file_name().unwrap().to_os_string().into_string().unwrap();
+                            let goal = match path.extension().and_then(|x| x.to_str()) {
+                                Some("dfa") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+                                Some("jff") => DFA::load_jflap_from_string(&contents).map_err(|e| e.to_string()),
+                                _ => {let _ = self.e_reporter.send(Error {title : "Unrecognized file type".to_owned(),body : RichText::new("Only .jff and .dfa files can be parsed")}); continue;}
+                            };
+                            match goal {
+                                Ok(goal) => {self.goal = goal;self.goal_pick = ExampleGoals::Custom(name);},
+                                Err(e) => {let _ = self.e_reporter.send(Error {title : format!("Could not open {}", name), body : RichText::new(e)});}
                             }
                         }
                         OpenItem::SRS => {
                             let path = PathBuf::from(fh.file_name());
                             self.ruleset_pick = ExampleRulesets::Custom(path.file_name().unwrap().to_os_string().into_string().unwrap());
                             self.srs_text = contents;
+                            self.srs_dir = file_dir(&fh);
+                        }
+                        OpenItem::Project => {
+                            match Project::from_json(&contents) {
+                                Ok(project) => self.load_project(project, &fh),
+                                Err(e) => {let _ = self.e_reporter.send(Error {title : "Invalid project file".to_owned(), body : RichText::new(e.to_string())});}
+                            }
+                        }
+                        OpenItem::TestedDFA => {
+                            let name = PathBuf::from(fh.file_name()).file_name().unwrap().to_os_string().into_string().unwrap();
+                            match serde_json::from_str(&contents) {
+                                Ok(dfa) => self.loaded_tested_dfa = Some((name,dfa)),
+                                Err(e) => {let _ = self.e_reporter.send(Error {title : format!("Could not open {}", name), body : RichText::new(e.to_string())});}
+                            }
                         }
                     }
                 }
@@ -251,6 +354,21 @@ impl PrepPanel {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn file_dir(fh : &FileHandle) -> Option<PathBuf> {
+    fh.path().parent().map(|p| p.to_path_buf())
+}
+
+//Browsers don't give us a real path, so includes can't be followed there
+#[cfg(target_arch = "wasm32")]
+fn file_dir(_fh : &FileHandle) -> Option<PathBuf> {
+    None
+}
+
+fn read_include(srs_dir : &Option<PathBuf>, include : &str) -> Option<String> {
+    std::fs::read_to_string(srs_dir.as_ref()?.join(include)).ok()
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn save_srs(input : String) {
     use super::execute;
@@ -265,4 +383,20 @@ fn save_srs(input : String) {
         }
     };
     execute(async_f);
-}
\ No newline at end of file
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_project_file(input : String) {
+    use super::execute;
+
+
+    let task = rfd::AsyncFileDialog::new().set_file_name("project.srsproj").add_filter("SRS projects", &["srsproj"]).save_file();
+    let async_f = async move {
+        let opened_file_r = task.await;
+        
+        if let Some(opened_file) = opened_file_r {
+            opened_file.write(input.as_bytes()).await.unwrap();
+        }
+    };
+    execute(async_f);
+}
@@ -5,6 +5,8 @@ use crate::{
     util::{Ruleset, SymbolIdx, SymbolSet, DFA},
 };
 
+pub mod tracks;
+
 pub fn build_1dpeg_result() -> DFA {
     let mut accepting_vec = vec![false; 18];
     accepting_vec[2] = true;
@@ -252,203 +254,23 @@ pub fn build_flip_rs() -> Ruleset {
 }
 
 pub fn build_flipx3_rs() -> Ruleset {
-    let b_symbol_set = SymbolSet {
-        length: 2,
-        representations: vec!["0".to_owned(), "1".to_owned()],
-    };
-    let mut rules_vec = vec![];
-    for i in 0..8 {
-        rules_vec.push((
-            vec![i / 4 % 2, i / 2 % 2, i % 2],
-            vec![1 - i / 4 % 2, 1 - i / 2 % 2, 1 - i % 2],
-        ))
-    }
-
-    let k = 3;
-    let symbol_num = 2_u32.pow(k as u32) as usize;
-    let mut new_rules = vec![];
-    let mut vert_starts;
-    let mut vert_ends;
-    for rule in rules_vec {
-        //Horizontal (single-symbol) rules
-        for i in 0..(rule.0.len() - k + 1) {
-            let mut start_sym_idx = 0;
-            for (rule_sym_idx, rule_sym) in rule.0.iter().enumerate() {
-                start_sym_idx += rule_sym
-                    * (b_symbol_set.length as SymbolIdx)
-                        .pow((rule.0.len() - rule_sym_idx - 1) as u32);
-            }
-            start_sym_idx *= (b_symbol_set.length as SymbolIdx).pow((i) as u32);
-
-            let mut end_sym_idx = 0;
-            for (rule_sym_idx, rule_sym) in rule.1.iter().enumerate() {
-                end_sym_idx += rule_sym
-                    * (b_symbol_set.length as SymbolIdx)
-                        .pow((rule.1.len() - rule_sym_idx - 1) as u32);
-            }
-            end_sym_idx *= (b_symbol_set.length as SymbolIdx).pow(i as u32);
-            new_rules.push((vec![start_sym_idx], vec![end_sym_idx]));
-        }
-        //Vertical (normal symbol length) rules
-        //i is horizontal index selected
-        //Represents the fixed column we're doing business with
-
-        for i in 0..k {
-            //LHS and RHS respectively
-            vert_starts = vec![vec![0; rule.0.len()]];
-            vert_ends = vec![vec![0; rule.1.len()]];
-            //j is vertical index selected
-            for j in 0..k {
-                let cur_vert_rules = vert_starts.len();
-                let pow_num = (b_symbol_set.length as SymbolIdx).pow(j as u32);
-
-                //If we're looking at the fixed row
-                if i == j {
-                    for start_idx in 0..cur_vert_rules {
-                        for vert_idx in 0..vert_starts[start_idx].len() {
-                            vert_starts[start_idx][vert_idx] += rule.0[vert_idx] * pow_num;
-                        }
-                        for vert_idx in 0..vert_ends[start_idx].len() {
-                            vert_ends[start_idx][vert_idx] += rule.1[vert_idx] * pow_num;
-                        }
-                    }
-                } else {
-                    for start_idx in 0..cur_vert_rules {
-                        for k in 1..symbol_num {
-                            let mut new_vert_start = vert_starts[start_idx].clone();
-                            let mut new_vert_end = vert_ends[start_idx].clone();
-                            for l in 0..k {
-                                if (k >> l) % 2 == 1 {
-                                    new_vert_start[l] += pow_num;
-                                    new_vert_end[l] += pow_num;
-                                }
-                            }
-
-                            vert_starts.push(new_vert_start);
-                            vert_ends.push(new_vert_end);
-                        }
-                    }
-                }
-            }
-            for i in 0..vert_starts.len() {
-                new_rules.push((vert_starts[i].clone(), vert_ends[i].clone()));
-            }
-        }
-    }
-    let by_k_symbol_set = SymbolSet {
-        length: 2_u32.pow(k as u32) as usize,
-        representations: vec![
-            "000".to_owned(),
-            "001".to_owned(),
-            "010".to_owned(),
-            "011".to_owned(),
-            "100".to_owned(),
-            "101".to_owned(),
-            "110".to_owned(),
-            "111".to_owned(),
-        ], //whoops! lol
-    };
-
-    Ruleset::from_vec(new_rules, by_k_symbol_set.clone())
+    //Safe unwrap, flipping is length-preserving
+    tracks::lift_ruleset(
+        &build_flip_rs(),
+        3,
+        &[tracks::Move::Horizontal, tracks::Move::Vertical],
+    )
+    .unwrap()
 }
 
 pub fn build_default2dpegx3_rs() -> Ruleset {
-    let b_symbol_set = SymbolSet {
-        length: 2,
-        representations: vec!["0".to_owned(), "1".to_owned()],
-    };
-
-    let rule_vec = vec![
-        (vec![1, 1, 0], vec![0, 0, 1]),
-        (vec![0, 1, 1], vec![1, 0, 0]),
-    ];
-
-    let k = 3;
-    let symbol_num = 2_u32.pow(k as u32) as usize;
-    let mut new_rules = vec![];
-    let mut vert_starts;
-    let mut vert_ends;
-    for rule in rule_vec {
-        //Horizontal (single-symbol) rules
-        for i in 0..(rule.0.len() - k + 1) {
-            let mut start_sym_idx = 0;
-            for (rule_sym_idx, rule_sym) in rule.0.iter().enumerate() {
-                start_sym_idx += rule_sym
-                    * (b_symbol_set.length as SymbolIdx)
-                        .pow((rule.0.len() - rule_sym_idx - 1) as u32);
-            }
-            start_sym_idx *= (b_symbol_set.length as SymbolIdx).pow((i) as u32);
-
-            let mut end_sym_idx = 0;
-            for (rule_sym_idx, rule_sym) in rule.1.iter().enumerate() {
-                end_sym_idx += rule_sym
-                    * (b_symbol_set.length as SymbolIdx)
-                        .pow((rule.1.len() - rule_sym_idx - 1) as u32);
-            }
-            end_sym_idx *= (b_symbol_set.length as SymbolIdx).pow(i as u32);
-            new_rules.push((vec![start_sym_idx], vec![end_sym_idx]));
-        }
-        //Vertical (normal symbol length) rules
-        //i is horizontal index selected
-        //Represents the fixed column we're doing business with
-
-        for i in 0..k {
-            //LHS and RHS respectively
-            vert_starts = vec![vec![0; rule.0.len()]];
-            vert_ends = vec![vec![0; rule.1.len()]];
-            //j is vertical index selected
-            for j in 0..k {
-                let cur_vert_rules = vert_starts.len();
-                let pow_num = (b_symbol_set.length as SymbolIdx).pow(j as u32);
-
-                //If we're looking at the fixed row
-                if i == j {
-                    for start_idx in 0..cur_vert_rules {
-                        for vert_idx in 0..vert_starts[start_idx].len() {
-                            vert_starts[start_idx][vert_idx] += rule.0[vert_idx] * pow_num;
-                        }
-                        for vert_idx in 0..vert_ends[start_idx].len() {
-                            vert_ends[start_idx][vert_idx] += rule.1[vert_idx] * pow_num;
-                        }
-                    }
-                } else {
-                    for start_idx in 0..cur_vert_rules {
-                        for k in 1..symbol_num {
-                            let mut new_vert_start = vert_starts[start_idx].clone();
-                            let mut new_vert_end = vert_ends[start_idx].clone();
-                            for l in 0..k {
-                                if (k >> l) % 2 == 1 {
-                                    new_vert_start[l] += pow_num;
-                                    new_vert_end[l] += pow_num;
-                                }
-                            }
-
-                            vert_starts.push(new_vert_start);
-                            vert_ends.push(new_vert_end);
-                        }
-                    }
-                }
-            }
-            for i in 0..vert_starts.len() {
-                new_rules.push((vert_starts[i].clone(), vert_ends[i].clone()));
-            }
-        }
-    }
-    let by_k_symbol_set = SymbolSet {
-        length: 2_u32.pow(k as u32) as usize,
-        representations: vec![
-            "000".to_owned(),
-            "001".to_owned(),
-            "010".to_owned(),
-            "011".to_owned(),
-            "100".to_owned(),
-            "101".to_owned(),
-            "110".to_owned(),
-            "111".to_owned(),
-        ], //whoops! lol
-    };
-
-    Ruleset::from_vec(new_rules, by_k_symbol_set.clone())
+    //Safe unwrap, peg moves are length-preserving
+    tracks::lift_ruleset(
+        &build_1dpeg_rs(),
+        3,
+        &[tracks::Move::Horizontal, tracks::Move::Vertical],
+    )
+    .unwrap()
 }
 
 pub fn build_threerulesolver<S>() -> Result<S, DomainError>
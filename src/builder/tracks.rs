@@ -0,0 +1,247 @@
+//Column encodings of m×N boards.
+//A board with m rows is written as a string of columns, where each column is a single symbol.
+//Row j of a column holds the j-th digit (base = size of the 1D symbol set) of that symbol's index,
+//so row 0 is the least significant digit and the rightmost part of the symbol's representation.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    solver::DomainError,
+    util::{Anchor, Ruleset, SymbolIdx, SymbolSet, DFA, MAX_SYMBOLS},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    //A 1D rule applied along a single row, leaving the other rows untouched
+    Horizontal,
+    //A 1D rule applied within a single column, read from the top row down
+    Vertical,
+}
+
+//Symbol set whose symbols are every possible column of a board with `rows` rows.
+//Fails if there are more columns than SymbolIdx can hold (see the wide_symbols feature)
+pub fn column_symset(symset: &SymbolSet, rows: usize) -> Result<SymbolSet, DomainError> {
+    let column_count = column_count(symset, rows)?;
+    let representations = (0..column_count)
+        .map(|column| {
+            (0..rows)
+                .rev()
+                .map(|row| &symset.representations[column_digit(column, row, symset.length)][..])
+                .collect::<String>()
+        })
+        .collect();
+    Ok(SymbolSet {
+        length: column_count,
+        representations,
+    })
+}
+
+//Lifts a 1D ruleset to a board with `rows` rows, applying its rules in each of the given directions.
+//Rules must be length-preserving, as the rows they don't touch have to stay aligned.
+pub fn lift_ruleset(rules: &Ruleset, rows: usize, moves: &[Move]) -> Result<Ruleset, DomainError> {
+    if let Some(rule) = rules.has_generating_rule() {
+        return Err(DomainError::Generating(rule));
+    }
+    if let Some(rule) = rules.has_deleting_rule() {
+        return Err(DomainError::Deleting(rule));
+    }
+    let base = rules.symbol_set.length;
+    let column_set = column_symset(&rules.symbol_set, rows)?;
+    //Sets instead of vecs, as rules of length 1 are identical in both directions
+    let mut new_rules = HashSet::new();
+
+    //Rules are sorted so that the result doesn't depend on HashMap ordering
+    let mut old_rules: Vec<(Option<Anchor>, &Vec<SymbolIdx>, &Vec<SymbolIdx>)> = rules
+        .rules
+        .iter()
+        .flat_map(|(lhs, rhs_list)| rhs_list.iter().map(move |rhs| (None, lhs, rhs)))
+        .chain(
+            rules
+                .anchored_rules
+                .iter()
+                .map(|(anchor, lhs, rhs)| (Some(*anchor), lhs, rhs)),
+        )
+        .collect();
+    old_rules.sort();
+
+    for (anchor, lhs, rhs) in old_rules {
+        let len = lhs.len();
+        if moves.contains(&Move::Horizontal) {
+            //Number of possible columns once the rule's row is taken out
+            let other_columns = base.pow((rows - 1) as u32);
+            for row in 0..rows {
+                let row_weight = base.pow(row as u32);
+                //Every possible set of contents for the other rows, one column of them at a time
+                for others in 0..other_columns.pow(len as u32) {
+                    let mut new_lhs = vec![];
+                    let mut new_rhs = vec![];
+                    for col in 0..len {
+                        let fixed =
+                            spread_column(column_digit(others, col, other_columns), row, base);
+                        new_lhs.push((fixed + lhs[col] as usize * row_weight) as SymbolIdx);
+                        new_rhs.push((fixed + rhs[col] as usize * row_weight) as SymbolIdx);
+                    }
+                    new_rules.insert((anchor, new_lhs, new_rhs));
+                }
+            }
+        }
+        if moves.contains(&Move::Vertical) && len <= rows {
+            for top in 0..=(rows - len) {
+                //Anchors pin vertical rules to the top ("^") or bottom ("$") of the column
+                if anchor.is_some_and(|a| a.at_start()) && top + len != rows
+                    || anchor.is_some_and(|a| a.at_end()) && top != 0
+                {
+                    continue;
+                }
+                //Every possible set of contents for the rows the rule doesn't cover
+                for others in 0..base.pow((rows - len) as u32) {
+                    let mut new_lhs = 0;
+                    let mut new_rhs = 0;
+                    let mut other_idx = 0;
+                    for row in 0..rows {
+                        let row_weight = base.pow(row as u32);
+                        if row >= top && row < top + len {
+                            //The first symbol of the rule goes in the highest row it covers
+                            new_lhs += lhs[top + len - 1 - row] as usize * row_weight;
+                            new_rhs += rhs[top + len - 1 - row] as usize * row_weight;
+                        } else {
+                            let other = column_digit(others, other_idx, base) * row_weight;
+                            new_lhs += other;
+                            new_rhs += other;
+                            other_idx += 1;
+                        }
+                    }
+                    new_rules.insert((
+                        None,
+                        vec![new_lhs as SymbolIdx],
+                        vec![new_rhs as SymbolIdx],
+                    ));
+                }
+            }
+        }
+    }
+    let mut new_rules: Vec<_> = new_rules.into_iter().collect();
+    new_rules.sort();
+    let mut unanchored: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>> = HashMap::new();
+    let mut anchored = vec![];
+    for (anchor, lhs, rhs) in new_rules {
+        match anchor {
+            Some(anchor) => anchored.push((anchor, lhs, rhs)),
+            None => unanchored.entry(lhs).or_default().push(rhs),
+        }
+    }
+    Ok(Ruleset::with_anchored_rules(
+        unanchored, anchored, column_set,
+    ))
+}
+
+//A board is accepted if exactly one of its rows is accepted by `goal` and every other row is made up of only `blank`.
+//e.g. lifting build_onlyone1 with blank 0 accepts boards with exactly one peg in total.
+pub fn lift_goal(goal: &DFA, rows: usize, blank: SymbolIdx) -> Result<DFA, DomainError> {
+    let base = goal.symbol_set.length;
+    //Each row tracks where the goal DFA would be if it were the chosen row, or None if it can't be anymore
+    lift_dfa(
+        goal,
+        rows,
+        vec![Some(goal.starting_state); rows],
+        |states: &Vec<Option<usize>>, column| {
+            (0..rows)
+                .map(|row| {
+                    let others_blank = (0..rows).all(|other| {
+                        other == row || column_digit(column, other, base) == blank as usize
+                    });
+                    match states[row] {
+                        Some(state) if others_blank => {
+                            Some(goal.state_transitions[state][column_digit(column, row, base)])
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        },
+        |states| {
+            states
+                .iter()
+                .any(|state| matches!(state, Some(state) if goal.accepting_states[*state]))
+        },
+    )
+}
+
+//A board is accepted if every one of its rows is accepted by `goal`
+pub fn lift_goal_each_row(goal: &DFA, rows: usize) -> Result<DFA, DomainError> {
+    let base = goal.symbol_set.length;
+    lift_dfa(
+        goal,
+        rows,
+        vec![goal.starting_state; rows],
+        |states: &Vec<usize>, column| {
+            (0..rows)
+                .map(|row| goal.state_transitions[states[row]][column_digit(column, row, base)])
+                .collect()
+        },
+        |states| states.iter().all(|state| goal.accepting_states[*state]),
+    )
+}
+
+//Builds the (minimized) column DFA reachable from `start`, where each state is some per-row bookkeeping
+fn lift_dfa<T, F, A>(
+    goal: &DFA,
+    rows: usize,
+    start: T,
+    step: F,
+    accepting: A,
+) -> Result<DFA, DomainError>
+where
+    T: Clone + Eq + std::hash::Hash,
+    F: Fn(&T, usize) -> T,
+    A: Fn(&T) -> bool,
+{
+    let symbol_set = column_symset(&goal.symbol_set, rows)?;
+    let mut state_idxs = HashMap::new();
+    let mut states = vec![start.clone()];
+    state_idxs.insert(start, 0);
+    let mut state_transitions = vec![];
+    while state_transitions.len() < states.len() {
+        let cur = states[state_transitions.len()].clone();
+        let mut transitions = Vec::with_capacity(symbol_set.length);
+        for column in 0..symbol_set.length {
+            let next = step(&cur, column);
+            let next_idx = match state_idxs.get(&next) {
+                Some(idx) => *idx,
+                None => {
+                    state_idxs.insert(next.clone(), states.len());
+                    states.push(next);
+                    states.len() - 1
+                }
+            };
+            transitions.push(next_idx);
+        }
+        state_transitions.push(transitions);
+    }
+    let mut result = DFA {
+        starting_state: 0,
+        state_transitions,
+        accepting_states: states.iter().map(accepting).collect(),
+        symbol_set,
+    };
+    result.minimize();
+    Ok(result)
+}
+
+fn column_count(symset: &SymbolSet, rows: usize) -> Result<usize, DomainError> {
+    match symset.length.checked_pow(rows as u32) {
+        Some(count) if count <= MAX_SYMBOLS => Ok(count),
+        count => Err(DomainError::TooManySymbols(count.unwrap_or(usize::MAX))),
+    }
+}
+
+//The symbol in row `row` of a column
+fn column_digit(column: usize, row: usize, base: usize) -> usize {
+    column / base.pow(row as u32) % base
+}
+
+//Turns a column of rows - 1 rows into a column of `rows` rows, with a zero inserted at `row`
+fn spread_column(column: usize, row: usize, base: usize) -> usize {
+    let low = column % base.pow(row as u32);
+    let high = column - low;
+    low + high * base
+}
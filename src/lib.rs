@@ -1,5 +1,6 @@
 pub mod builder;
 
+pub mod project;
 pub mod solver;
 pub mod test;
 pub mod util;
@@ -0,0 +1,108 @@
+//Project files (.srsproj) bundle everything needed to reproduce a run: the rules, the goal,
+//the solver settings, and the resulting DFA if there is one. Stored as JSON.
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Result;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+
+use crate::solver::{
+    BFSSolver, DomainError, HashSolver, Instant, MinkidSolver, SRSSolver, SubsetSolver,
+};
+use crate::util::{Ruleset, DFA};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SolverType {
+    Minkid,
+    Subset,
+    BFS,
+    Hash,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectResult {
+    pub dfa: DFA,
+    //The k the DFA was generated with. When verifying, the lowest k that gave the same DFA as k+1
+    pub sig_k: usize,
+    //Wall-clock time of the whole run, verification included
+    pub duration: Duration,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub rules: Ruleset,
+    pub goal: DFA,
+    pub sig_k: usize,
+    pub solver: SolverType,
+    //Whether to keep raising k until the result stops changing
+    pub verify: bool,
+    pub result: Option<ProjectResult>,
+}
+
+impl Project {
+    pub fn new(rules: Ruleset, goal: DFA, sig_k: usize, solver: SolverType) -> Self {
+        Project {
+            rules,
+            goal,
+            sig_k,
+            solver,
+            verify: true,
+            result: None,
+        }
+    }
+
+    pub fn from_json(input: &str) -> Result<Self> {
+        serde_json::from_str(input)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(file: &mut File) -> Result<Self> {
+        use std::io::BufReader;
+        serde_json::from_reader(BufReader::new(file))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, file: &mut File) -> Result<()> {
+        let writer = std::io::BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    //Runs the chosen solver with the project's settings, replacing any earlier result
+    pub fn run(&mut self) -> std::result::Result<&ProjectResult, DomainError> {
+        let start = Instant::now();
+        let (dfa, sig_k) = match self.solver {
+            SolverType::Minkid => self.run_with::<MinkidSolver>()?,
+            SolverType::Subset => self.run_with::<SubsetSolver>()?,
+            SolverType::BFS => self.run_with::<BFSSolver>()?,
+            SolverType::Hash => self.run_with::<HashSolver>()?,
+        };
+        Ok(self.result.insert(ProjectResult {
+            dfa,
+            sig_k,
+            duration: start.elapsed(),
+        }))
+    }
+
+    fn run_with<S: SRSSolver>(&self) -> std::result::Result<(DFA, usize), DomainError> {
+        let solver = S::new(self.rules.clone(), self.goal.clone())?;
+        let mut sig_k = self.sig_k;
+        let mut dfa = solver.run(sig_k, vec![]);
+        if self.verify {
+            loop {
+                let next = solver.run(sig_k + 1, vec![]);
+                if next == dfa {
+                    break;
+                }
+                dfa = next;
+                sig_k += 1;
+            }
+        }
+        Ok((dfa, sig_k))
+    }
+}
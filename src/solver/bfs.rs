@@ -20,7 +20,7 @@ use crate::solver::srssolver::DomainError;
 use bitvec::prelude::*;
 
 #[derive(Clone)]
-pub struct BFSSolver<State = Vec<u8>, Input = String, Output = bool>
+pub struct BFSSolver<State = Vec<SymbolIdx>, Input = String, Output = bool>
 where
     State: Clone,
 {
@@ -139,7 +139,7 @@ where
             let mut chunks_dispatched = 0;
             let chunk_length = self.symbol_set.sig_set_size(sig_k) / chunks_per_sig_set;
             for (start_idx, board) in &old_boards {
-                for sym_idx in 0..(self.symbol_set.length as SymbolIdx) {
+                for sym_idx in (0..self.symbol_set.length).map(|x| x as SymbolIdx) {
                     let new_board = self.mutate(board.clone(), sym_idx);
 
                     new_state_to_ctx.insert(new_board.clone(), EvaluatedState {origin_idx: *start_idx,
@@ -283,7 +283,7 @@ where
         output: &Arc<SegQueue<(Output, usize)>>,
     ) -> Vec<(Vec<Output>, State)> {
         let mut results = Vec::with_capacity(self.symbol_set.length);
-        for sym in 0..(self.symbol_set.length as SymbolIdx) {
+        for sym in (0..self.symbol_set.length).map(|x| x as SymbolIdx) {
             let mut new_board = board.clone();
             new_board = self.mutate(new_board, sym);
             results.push((
@@ -45,11 +45,11 @@ impl Solver for HashSolver {
         &self.rules.symbol_set
     }
     const PHASES: &'static [&'static str] = &["Entire Iteration"];
-    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<u8>) -> bool {
+    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<SymbolIdx>) -> bool {
         todo!()
     }
 
-    fn mutate(&self, state: Vec<u8>, input: SymbolIdx) -> Vec<u8> {
+    fn mutate(&self, state: Vec<SymbolIdx>, input: SymbolIdx) -> Vec<SymbolIdx> {
         todo!()
     }
     fn run_internal(
@@ -220,7 +220,7 @@ impl HashSolver {
         sig_set: &Vec<Vec<SymbolIdx>>,
     ) -> Vec<(BitVec, Vec<SymbolIdx>)> {
         let mut results = Vec::with_capacity(self.rules.symbol_set.length);
-        for sym in 0..(self.rules.symbol_set.length as SymbolIdx) {
+        for sym in (0..self.rules.symbol_set.length).map(|x| x as SymbolIdx) {
             let mut new_board = board.clone();
             new_board.push(sym);
             results.push((self.sig_with_set(&new_board, sig_set), new_board));
@@ -13,7 +13,7 @@ use petgraph::{
     Graph,
 };
 
-use crate::util::{Ruleset, SymbolIdx, DFA};
+use crate::util::{Ruleset, SymbolIdx, Symmetry, DFA};
 
 use super::{DFAStructure, Instant, SRSSolver, SSStructure, Solver};
 use crate::solver::srssolver::DomainError;
@@ -45,6 +45,9 @@ pub struct MinkidSolver {
 
     //Lookup table of where individual ss elements ended up in the graph
     ss_idx_to_link: Vec<NodeIndex>,
+
+    //Symmetries of the rules and goal used to skip work. Empty unless set with with_symmetries
+    symmetries: Vec<Symmetry>,
 }
 
 struct MKDFAState {
@@ -68,6 +71,7 @@ impl SRSSolver for MinkidSolver {
             goal_minkids: vec![],
             ss_link_graph: Graph::new(),
             ss_idx_to_link: vec![],
+            symmetries: vec![],
         })
     }
 
@@ -87,11 +91,11 @@ impl Solver for MinkidSolver {
         "Remove duplicates",
     ];
 
-    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<u8>) -> bool {
+    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<SymbolIdx>) -> bool {
         todo!();
     }
 
-    fn mutate(&self, state: Vec<u8>, input: SymbolIdx) -> Vec<u8> {
+    fn mutate(&self, state: Vec<SymbolIdx>, input: SymbolIdx) -> Vec<SymbolIdx> {
         SRSSolver::mutate(self, state, input)
     }
 
@@ -108,7 +112,7 @@ impl Solver for MinkidSolver {
         //Usize is index in trans_table
 
         assert!(
-            core::cmp::PartialEq::<Vec<u8>>::eq(&origin, &vec![]),
+            core::cmp::PartialEq::<Vec<SymbolIdx>>::eq(&origin, &vec![]),
             "doesn't support non-null origin (sorry!)"
         );
 
@@ -116,12 +120,24 @@ impl Solver for MinkidSolver {
         self.build_ss_link_graph(sig_set);
         let real_self = Arc::new(self);
         let mut dfa_graph = DiGraph::<RwLock<MKDFAState>, SymbolIdx>::new();
-        let mut link_graph = DiGraph::<(), (Vec<SymbolIdx>, Vec<SymbolIdx>)>::new();
+        let mut link_graph = DiGraph::<(), (Vec<SymbolIdx>, Vec<SymbolIdx>, bool)>::new();
         dfa_graph.add_node(RwLock::new(MKDFAState {
-            minkids: real_self.goal_minkids[real_self.goal.starting_state].clone(),
+            minkids: real_self.root_minkids(&sig_set),
             goal_states: vec![real_self.goal.starting_state],
         }));
         link_graph.add_node(());
+        //Every rule as (LHS, RHS, whether it ends the string, whether it only applies from the root).
+        //Links from rules that end the string are exact -- they can't be extended by any more symbols
+        let mut rule_links = vec![];
+        for (lhs, rhs_list) in &real_self.rules.rules {
+            for rhs in rhs_list {
+                rule_links.push((lhs.clone(), rhs.clone(), false, false));
+            }
+        }
+        for (anchor, lhs, rhs) in &real_self.rules.anchored_rules {
+            rule_links.push((lhs.clone(), rhs.clone(), anchor.at_end(), anchor.at_start()));
+        }
+        let has_start_rules = rule_links.iter().any(|(_, _, _, from_root)| *from_root);
         //number of nodes after an iteration.
         //Each iteration only works if there are two lengths -- so we start with two.
         let mut iteration_lens = vec![0, 1];
@@ -145,7 +161,7 @@ impl Solver for MinkidSolver {
             {
                 //Root node that prospective state will be connected to
                 let start_node = NodeIndex::new(start_idx);
-                for next_sym in 0..(real_self.rules.symbol_set.length as SymbolIdx) {
+                for next_sym in (0..real_self.rules.symbol_set.length).map(|x| x as SymbolIdx) {
                     //states that the prospective state can reach into the goal DFA
                     let mut goal_connections = vec![];
                     //Set of minimum kids that can be added without any SRS applications.
@@ -199,80 +215,85 @@ impl Solver for MinkidSolver {
             {
                 //Root node that prospective state will be connected to
                 let start_node = NodeIndex::new(start_idx);
-                for rule_list in &real_self.rules.rules {
-                    let lhs_str = rule_list.0;
-                    for rhs_str in rule_list.1 {
-                        let mut lhs = vec![start_node];
-                        let mut rhs = vec![start_node];
-                        let mut p_rule_len = 1;
-                        while p_rule_len <= lhs_str.len() || p_rule_len <= rhs_str.len() {
-                            let mut potential_lhs_e = vec![];
-
-                            for lhs_e in &lhs {
-                                if p_rule_len <= lhs_str.len() {
-                                    if let Some(e) = dfa_graph
+                //The root is the only node that represents the start of the string
+                for (lhs_str, rhs_str, exact, _) in rule_links
+                    .iter()
+                    .filter(|(_, _, _, from_root)| !from_root || start_idx == 0)
+                {
+                    let mut lhs = vec![start_node];
+                    let mut rhs = vec![start_node];
+                    let mut p_rule_len = 1;
+                    while p_rule_len <= lhs_str.len() || p_rule_len <= rhs_str.len() {
+                        let mut potential_lhs_e = vec![];
+
+                        for lhs_e in &lhs {
+                            if p_rule_len <= lhs_str.len() {
+                                if let Some(e) = dfa_graph
+                                    .edges_directed(*lhs_e, Outgoing)
+                                    .find(|x| *x.weight() == lhs_str[p_rule_len - 1])
+                                {
+                                    potential_lhs_e.push(e.target())
+                                }
+                            } else if *exact {
+                                //Nothing can come after the end of the string, so the finished side stays put
+                                potential_lhs_e.push(*lhs_e);
+                            } else {
+                                potential_lhs_e.extend(
+                                    dfa_graph
                                         .edges_directed(*lhs_e, Outgoing)
-                                        .find(|x| *x.weight() == lhs_str[p_rule_len - 1])
-                                    {
-                                        potential_lhs_e.push(e)
-                                    }
-                                } else {
-                                    potential_lhs_e.append(
-                                        &mut dfa_graph.edges_directed(*lhs_e, Outgoing).collect(),
-                                    );
-                                };
-                            }
+                                        .map(|e| e.target()),
+                                );
+                            };
+                        }
 
-                            let mut potential_rhs_e = vec![];
+                        let mut potential_rhs_e = vec![];
 
-                            for rhs_e in &rhs {
-                                if p_rule_len <= rhs_str.len() {
-                                    if let Some(e) = dfa_graph
-                                        .edges_directed(*rhs_e, Outgoing)
-                                        .find(|x| *x.weight() == rhs_str[p_rule_len - 1])
-                                    {
-                                        potential_rhs_e.push(e)
-                                    }
-                                } else {
-                                    potential_rhs_e.append(
-                                        &mut dfa_graph.edges_directed(*rhs_e, Outgoing).collect(),
-                                    );
-                                };
-                            }
-                            if potential_lhs_e.is_empty() || potential_rhs_e.is_empty() {
-                                break;
-                            }
-                            lhs.clear();
-                            rhs.clear();
-                            let lhs_strip = if p_rule_len < lhs_str.len() {
-                                &lhs_str[p_rule_len..]
-                            } else {
-                                &lhs_str[..0]
-                            };
-                            let rhs_strip = if p_rule_len < rhs_str.len() {
-                                &rhs_str[p_rule_len..]
+                        for rhs_e in &rhs {
+                            if p_rule_len <= rhs_str.len() {
+                                if let Some(e) = dfa_graph
+                                    .edges_directed(*rhs_e, Outgoing)
+                                    .find(|x| *x.weight() == rhs_str[p_rule_len - 1])
+                                {
+                                    potential_rhs_e.push(e.target())
+                                }
+                            } else if *exact {
+                                potential_rhs_e.push(*rhs_e);
                             } else {
-                                &rhs_str[..0]
+                                potential_rhs_e.extend(
+                                    dfa_graph
+                                        .edges_directed(*rhs_e, Outgoing)
+                                        .map(|e| e.target()),
+                                );
                             };
-                            for potential_lhs_edge in &potential_lhs_e {
-                                for potential_rhs_edge in &potential_rhs_e {
-                                    real_self.add_link(
-                                        &mut link_graph,
-                                        potential_lhs_edge.target(),
-                                        potential_rhs_edge.target(),
-                                        lhs_strip,
-                                        rhs_strip,
-                                    );
-                                }
-                            }
-                            for potential_lhs_edge in potential_lhs_e {
-                                lhs.push(potential_lhs_edge.target());
-                            }
-                            for potential_rhs_edge in potential_rhs_e {
-                                rhs.push(potential_rhs_edge.target());
+                        }
+                        if potential_lhs_e.is_empty() || potential_rhs_e.is_empty() {
+                            break;
+                        }
+                        let lhs_strip = if p_rule_len < lhs_str.len() {
+                            &lhs_str[p_rule_len..]
+                        } else {
+                            &lhs_str[..0]
+                        };
+                        let rhs_strip = if p_rule_len < rhs_str.len() {
+                            &rhs_str[p_rule_len..]
+                        } else {
+                            &rhs_str[..0]
+                        };
+                        for potential_lhs_node in &potential_lhs_e {
+                            for potential_rhs_node in &potential_rhs_e {
+                                real_self.add_link(
+                                    &mut link_graph,
+                                    *potential_lhs_node,
+                                    *potential_rhs_node,
+                                    lhs_strip,
+                                    rhs_strip,
+                                    *exact,
+                                );
                             }
-                            p_rule_len += 1;
                         }
+                        lhs = potential_lhs_e;
+                        rhs = potential_rhs_e;
+                        p_rule_len += 1;
                     }
                 }
             }
@@ -292,6 +313,7 @@ impl Solver for MinkidSolver {
                     let target = link_graph.edge_endpoints(real_edge).unwrap().1;
                     if !(link_graph[real_edge].0.is_empty()
                         && link_graph[real_edge].1.is_empty()
+                        && !link_graph[real_edge].2
                         && target.index() < iteration_lens[iteration_lens.len() - 1])
                     {
                         continue;
@@ -329,10 +351,15 @@ impl Solver for MinkidSolver {
                                     rhs_extension,
                                     &vec![][..],
                                     &vec![][..],
+                                    false,
                                 )
                                 .0
                             {
-                                link_graph.add_edge(lhs_extension, rhs_extension, (vec![], vec![]));
+                                link_graph.add_edge(
+                                    lhs_extension,
+                                    rhs_extension,
+                                    (vec![], vec![], false),
+                                );
                                 propagation_pairs.push((lhs_extension, rhs_extension));
                             }
                         }
@@ -462,6 +489,18 @@ impl Solver for MinkidSolver {
                 }
                 match equivalent_known {
                     Some(equiv) => {
+                        //Rules anchored to the start only link from the root, so nothing else carries what the
+                        //duplicate's goal connections would have given its children -- hand them to the survivor
+                        if has_start_rules {
+                            let pros_goals =
+                                dfa_graph[pros_node].read().unwrap().goal_states.clone();
+                            let equiv_goals = &mut dfa_graph[equiv].write().unwrap().goal_states;
+                            for goal_state in pros_goals {
+                                if !equiv_goals.contains(&goal_state) {
+                                    equiv_goals.push(goal_state);
+                                }
+                            }
+                        }
                         //Re-link if there exists an equivalent state
                         let disappointed_parent_edge = dfa_graph
                             .edges_directed(pros_node, Incoming)
@@ -489,6 +528,7 @@ impl Solver for MinkidSolver {
                                     equiv,
                                     &rust_scared.0[..],
                                     &rust_scared.1[..],
+                                    rust_scared.2,
                                 )
                                 .1
                             {
@@ -507,6 +547,7 @@ impl Solver for MinkidSolver {
                                     target,
                                     &rust_scared.0[..],
                                     &rust_scared.1[..],
+                                    rust_scared.2,
                                 )
                                 .1
                             {
@@ -561,6 +602,13 @@ impl Solver for MinkidSolver {
 }
 
 impl MinkidSolver {
+    //Lets runs skip working out strings that a symmetry sends to ones already worked out.
+    //Every symmetry given has to actually be one (see Symmetry::find_all), or the result will be wrong
+    pub fn with_symmetries(mut self, symmetries: Vec<Symmetry>) -> Self {
+        self.symmetries = symmetries;
+        self
+    }
+
     fn build_ss_link_graph(&mut self, sig_set: &Vec<Vec<SymbolIdx>>) {
         let mut ss_link_graph = DiGraph::<usize, ()>::with_capacity(sig_set.len(), 10);
         //irritated that there is not an immediately obvious better way but w/e
@@ -569,12 +617,51 @@ impl MinkidSolver {
         for i in 0..sig_set.len() {
             ss_link_graph.add_node(i);
         }
+        //Reversing symmetries don't apply, since elements always have something in front of them
+        let sig_images: Vec<Vec<usize>> = self
+            .symmetries
+            .iter()
+            .filter(|x| !x.reversed)
+            .map(|symmetry| {
+                sig_set
+                    .iter()
+                    .map(|x| {
+                        self.rules
+                            .symbol_set
+                            .find_in_sig_set(symmetry.apply(x).iter())
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut linked = bitvec![0; sig_set.len()];
         for i in 0..sig_set.len() {
-            for result in self.single_rule_hash(&sig_set[i]) {
-                let target_idx = self.rules.symbol_set.find_in_sig_set(result.iter());
-                if target_idx < sig_set.len() {
-                    ss_link_graph.add_edge(NodeIndex::new(i), NodeIndex::new(target_idx), ());
+            if linked[i] {
+                continue;
+            }
+            //Signature set elements come after some other string, so they're never at the start of it
+            let targets: Vec<usize> = self
+                .single_rule_hash_within(&sig_set[i], false)
+                .iter()
+                .map(|x| self.rules.symbol_set.find_in_sig_set(x.iter()))
+                .filter(|x| *x < sig_set.len())
+                .collect();
+            for target_idx in &targets {
+                ss_link_graph.add_edge(NodeIndex::new(i), NodeIndex::new(*target_idx), ());
+            }
+            linked.set(i, true);
+            //Each symmetry sends the rewrites of this element to the rewrites of its image
+            for images in &sig_images {
+                if linked[images[i]] {
+                    continue;
                 }
+                for target_idx in &targets {
+                    ss_link_graph.add_edge(
+                        NodeIndex::new(images[i]),
+                        NodeIndex::new(images[*target_idx]),
+                        (),
+                    );
+                }
+                linked.set(images[i], true);
             }
         }
         //Get rid of strongly-connected components
@@ -661,6 +748,28 @@ impl MinkidSolver {
         }*/
     }
 
+    //The root is the only state where signature set elements are the start of the string,
+    //so it's the only one that can solve elements using rules anchored to the start
+    fn root_minkids(&self, sig_set: &Vec<Vec<SymbolIdx>>) -> HashSet<NodeIndex> {
+        let mut minkids = self.goal_minkids[self.goal.starting_state].clone();
+        loop {
+            let mut new_kids = HashSet::new();
+            for (ss_idx, element) in sig_set.iter().enumerate() {
+                for application in self.anchored_rule_hash(element, true) {
+                    let result_idx = self.rules.symbol_set.find_in_sig_set(application.3.iter());
+                    if result_idx < sig_set.len()
+                        && self.check_if_ancestor(&minkids, self.ss_idx_to_link[result_idx])
+                    {
+                        new_kids.insert(self.ss_idx_to_link[ss_idx]);
+                    }
+                }
+            }
+            if !self.add_set_to_minkids(&mut minkids, &new_kids) {
+                return minkids;
+            }
+        }
+    }
+
     //Checks to see if a potentially new element of the minkid set is actually an ancestor to a pre-existing minkid
     //false means it is distinct from the current set
     fn check_if_ancestor(&self, min_children: &HashSet<NodeIndex>, potential: NodeIndex) -> bool {
@@ -737,7 +846,7 @@ impl MinkidSolver {
         self: &Arc<Self>,
         dfa_graph: &DiGraph<RwLock<MKDFAState>, SymbolIdx>,
         sig_set: &Vec<Vec<SymbolIdx>>,
-        connection: &(Vec<SymbolIdx>, Vec<SymbolIdx>),
+        connection: &(Vec<SymbolIdx>, Vec<SymbolIdx>, bool),
         lhs: NodeIndex,
         rhs: NodeIndex,
     ) -> bool {
@@ -759,9 +868,14 @@ impl MinkidSolver {
             while let Some(nx) = dfs.next(&reversed_graph) {
                 for ss_idx in &self.ss_link_graph[nx].original_idxs {
                     //If the ss element is actually big enough to comply with the obligation, and does
-                    if sig_set[*ss_idx].len() >= connection.1.len()
-                        && sig_set[*ss_idx][0..connection.1.len()] == connection.1
-                    {
+                    //(exact links come from rules that end the string, so the obligation has to be all there is)
+                    let complies = if connection.2 {
+                        sig_set[*ss_idx] == connection.1
+                    } else {
+                        sig_set[*ss_idx].len() >= connection.1.len()
+                            && sig_set[*ss_idx][0..connection.1.len()] == connection.1
+                    };
+                    if complies {
                         //Build what the new element would look like
                         let mut new_ss = connection.0.clone();
                         new_ss.extend(&sig_set[*ss_idx][connection.1.len()..]);
@@ -798,11 +912,12 @@ impl MinkidSolver {
 
     fn add_link(
         self: &Arc<Self>,
-        link_graph: &mut DiGraph<(), (Vec<SymbolIdx>, Vec<SymbolIdx>)>,
+        link_graph: &mut DiGraph<(), (Vec<SymbolIdx>, Vec<SymbolIdx>, bool)>,
         lhs: NodeIndex,
         rhs: NodeIndex,
         lhs_obligation: &[SymbolIdx],
         rhs_obligation: &[SymbolIdx],
+        exact: bool,
     ) -> (bool, bool) {
         let mut death_row = vec![];
         let mut should_add = true;
@@ -811,6 +926,10 @@ impl MinkidSolver {
         //1. we make any of them redundant by offering a more flexible alternative
         //2. any of them make our potential link redundant by already being more flexible
         for edge in link_graph.edges_connecting(lhs, rhs) {
+            //Exact and inexact links don't have comparable obligations
+            if edge.weight().2 != exact {
+                continue;
+            }
             //redundancy check!
 
             let lhs_min = std::cmp::min(lhs_obligation.len(), edge.weight().0.len());
@@ -833,7 +952,11 @@ impl MinkidSolver {
         }
         if should_add {
             //This has made me realize these could definitely just be references... but whatever!
-            link_graph.add_edge(lhs, rhs, (lhs_obligation.to_vec(), rhs_obligation.to_vec()));
+            link_graph.add_edge(
+                lhs,
+                rhs,
+                (lhs_obligation.to_vec(), rhs_obligation.to_vec(), exact),
+            );
         }
         for dead_edge in &death_row {
             link_graph.remove_edge(*dead_edge);
@@ -31,6 +31,9 @@ pub use self::subset::SubsetSolver;
 mod minkid;
 pub use self::minkid::MinkidSolver;
 
+mod moore;
+pub use self::moore::MooreSolver;
+
 mod solver;
 pub use self::solver::*;
 mod srssolver;
@@ -80,7 +83,7 @@ fn verify_to_len(&mut self,test_dfa : DFA, n:usize) -> bool{
         std::mem::swap(&mut signature_set_old, &mut signature_set_new);
         signature_set_new.clear();
         for (idx,i) in signature_set_old.iter().enumerate() {
-            for symbol in 0..(self.symbol_set.length as SymbolIdx) {
+            for symbol in (0..self.symbol_set.length).map(|x| x as SymbolIdx) {
                 signature_set_new.push(i.clone());
                 signature_set_new.last_mut().unwrap().push(symbol);
                 let test_board = signature_set_new.last().unwrap();
@@ -116,53 +119,5 @@ fn verify_to_len(&mut self,test_dfa : DFA, n:usize) -> bool{
 
     true
 
-}
-fn random_tests(&mut self,test_dfa : DFA, n:usize, total_boards:usize){
-    //almost certainly a constant time answer to this but idk and idc
-
-    println!("Starting DFA verification for {} strings of length {}.",total_boards, n);
-    let mut num_completed = 0;
-    let mut num_accepting = 0;
-    let mut start_index = 0;
-
-    let (input, output) = self.create_workers(WORKERS);
-
-    let mut test_items : Vec<Vec<SymbolIdx>> = vec![];
-    let mut rng = rand::thread_rng();
-    for i in 0..total_boards {
-        let mut new_board = vec![];
-        for _ in 0..n {
-            new_board.push(rng.gen_range(0..self.symbol_set.length) as SymbolIdx);
-        }
-        input.push((new_board.clone(),i));
-        test_items.push(new_board);
-    }
-
-    let mut num_recieved = 0;
-    while num_recieved < total_boards {
-        match output.pop() {
-        Some((bfs_result,idx)) => {
-            let test_board = &test_items[idx];
-            num_completed += 1;
-            num_recieved += 1;
-            if (num_completed) % (total_boards / 10) == 0 {
-                println!("{}% complete! ({} boards completed)", 100 * num_completed / total_boards, num_completed);
-            }
-            if bfs_result {num_accepting += 1}
-            if test_dfa.contains(&test_board) != bfs_result {
-                println!("Damn. DFA-solvability failed.");
-                println!("Problem board: {}",symbols_to_string(&test_board));
-                println!("DFA: {}, BFS: {}",!bfs_result,bfs_result);
-                return;
-            }
-        }
-        None => {std::thread::sleep(time::Duration::from_millis(100));}
-        }
-    }
-    self.terminate_workers(input, WORKERS);
-
-
-    println!("All verified! {}% accepting",(num_accepting as f64) * 100.0 / (total_boards as f64));
-
 }
  */
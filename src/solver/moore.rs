@@ -0,0 +1,61 @@
+use crate::util::{Ruleset, SymbolSet, DFA};
+
+use super::{DomainError, SRSSolver};
+
+//Solves for Moore machines instead of DFAs. The goal gives every string a value from a finite ordered set,
+//and the result gives every string the greatest value among the strings it can be rewritten into
+//(itself included). Questions like "the fewest pegs reachable" order their values with std::cmp::Reverse.
+//Being worth at least some value is an ordinary goal, so each value above the lowest gets its own solver
+#[derive(Clone)]
+pub struct MooreSolver<S, O> {
+    lowest: O,
+    //Increasing, each alongside a solver whose goal is the strings worth at least that much
+    levels: Vec<(O, S)>,
+    //What the solvers expanded the symbol set to, for goals with only the one value
+    symbol_set: SymbolSet,
+}
+
+impl<S, O> MooreSolver<S, O>
+where
+    S: SRSSolver,
+    O: Clone + Ord,
+{
+    pub fn new(ruleset: Ruleset, goal: DFA<String, O>) -> Result<Self, DomainError> {
+        let mut values = goal.outputs().into_iter();
+        let lowest = values.next().ok_or(DomainError::EmptyGoal)?;
+        let mut levels = vec![];
+        for value in values {
+            let solver = S::new(ruleset.clone(), goal.at_least(&value))?;
+            levels.push((value, solver));
+        }
+        let mut expanded = ruleset;
+        S::ensure_expansion(&mut expanded, &mut goal.at_least(&lowest));
+        Ok(MooreSolver {
+            lowest,
+            levels,
+            symbol_set: expanded.symbol_set,
+        })
+    }
+
+    pub fn run(&self, sig_k: usize) -> DFA<String, O> {
+        if self.levels.is_empty() {
+            return DFA {
+                starting_state: 0,
+                state_transitions: vec![vec![0; self.symbol_set.length]],
+                accepting_states: vec![self.lowest.clone()],
+                symbol_set: self.symbol_set.clone(),
+            };
+        }
+        let results: Vec<(O, DFA)> = self
+            .levels
+            .iter()
+            .map(|(value, solver)| (value.clone(), solver.run(sig_k, vec![])))
+            .collect();
+        DFA::from_levels(self.lowest.clone(), &results)
+    }
+
+    //The boolean solvers, lowest value first
+    pub fn get_levels(&self) -> &[(O, S)] {
+        &self.levels
+    }
+}
@@ -182,7 +182,7 @@ where
         };
         let mut rollover = self.cur_vec.len();
         while rollover > 0
-            && self.cur_vec[rollover - 1] == (self.solver.get_symset().length - 1) as u8
+            && self.cur_vec[rollover - 1] == (self.solver.get_symset().length - 1) as SymbolIdx
         {
             self.cur_vec[rollover - 1] = 0;
             rollover -= 1;
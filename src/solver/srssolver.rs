@@ -11,7 +11,7 @@ use std::thread;
 
 use crate::{
     test,
-    util::{Ruleset, SymbolIdx, SymbolSet, DFA},
+    util::{Anchor, Ruleset, SymbolIdx, SymbolSet, DFA, MAX_SYMBOLS},
 };
 
 use crate::solver::events::*;
@@ -89,7 +89,19 @@ where
     fn get_goal(&self) -> &DFA;
 
     fn single_rule_hash(&self, start_board: &Vec<SymbolIdx>) -> Vec<Vec<SymbolIdx>> {
+        self.single_rule_hash_within(start_board, true)
+    }
+    //at_start is false when start_board may have more symbols before it (e.g. a signature set element),
+    //in which case rules anchored to the start of the string can't be applied
+    fn single_rule_hash_within(
+        &self,
+        start_board: &Vec<SymbolIdx>,
+        at_start: bool,
+    ) -> Vec<Vec<SymbolIdx>> {
         let mut result = vec![];
+        for application in self.anchored_rule_hash(start_board, at_start) {
+            result.push(application.3);
+        }
         if start_board.is_empty() {
             if let Some(new_swaps) = self.get_ruleset().rules.get(&start_board[..]) {
                 for new_swap in new_swaps {
@@ -129,7 +141,7 @@ where
         &self,
         start_board: &Vec<SymbolIdx>,
     ) -> Vec<(usize, usize, usize, Vec<SymbolIdx>)> {
-        let mut result = vec![];
+        let mut result = self.anchored_rule_hash(start_board, true);
         if start_board.is_empty() {
             if let Some(new_swaps) = self.get_ruleset().rules.get(&start_board[..]) {
                 for new_swap in new_swaps {
@@ -162,11 +174,44 @@ where
         result
     }
 
+    //Same annotation as single_rule_hash_annotated, but only for the anchored rules
+    fn anchored_rule_hash(
+        &self,
+        start_board: &Vec<SymbolIdx>,
+        at_start: bool,
+    ) -> Vec<(usize, usize, usize, Vec<SymbolIdx>)> {
+        let mut result = vec![];
+        for (anchor, lhs, rhs) in &self.get_ruleset().anchored_rules {
+            if lhs.len() > start_board.len() || (anchor.at_start() && !at_start) {
+                continue;
+            }
+            let lftmst_idx = if anchor.at_start() {
+                0
+            } else {
+                start_board.len() - lhs.len()
+            };
+            if (anchor.at_end() && lftmst_idx + lhs.len() != start_board.len())
+                || start_board[lftmst_idx..(lftmst_idx + lhs.len())] != lhs[..]
+            {
+                continue;
+            }
+            let mut new_board = start_board[0..lftmst_idx].to_vec();
+            new_board.extend(rhs);
+            new_board.extend(&start_board[lftmst_idx + lhs.len()..]);
+            result.push((lftmst_idx, lhs.len(), rhs.len(), new_board));
+        }
+        result
+    }
+
     fn sized_init(rules: &Ruleset) -> (usize, usize) {
         let mut min_input: usize = usize::MAX;
         let mut max_input: usize = 0;
-        for i in &rules.rules {
-            let input_len = i.0.len();
+        let lhs_list = rules
+            .rules
+            .keys()
+            .chain(rules.anchored_rules.iter().map(|(_, lhs, _)| lhs));
+        for lhs in lhs_list {
+            let input_len = lhs.len();
             if input_len < min_input {
                 min_input = input_len;
             }
@@ -258,9 +303,23 @@ where
         }
         //See what single rule applications are possible when starting from any state in the DFA
         //Example -- create a path between (q0, 110) and (q0,001) if one does not exist
+        //Rules anchored to the start can only be applied from the starting state
+        let start_rules: Vec<_> = self
+            .get_ruleset()
+            .anchored_rules
+            .iter()
+            .filter(|(anchor, _, _)| *anchor == Anchor::Start)
+            .map(|(_, lhs, rhs)| (lhs, vec![rhs.clone()]))
+            .collect();
         for origin in 0..possible_dfa.state_transitions.len() {
             //For each state
-            for rule_list in &self.get_ruleset().rules {
+            let origin_rules = self.get_ruleset().rules.iter().chain(
+                start_rules
+                    .iter()
+                    .filter(|_| origin == possible_dfa.starting_state)
+                    .map(|(lhs, rhs)| (*lhs, rhs)),
+            );
+            for rule_list in origin_rules {
                 //For each rule
                 let lhs = rule_list.0;
                 //Drawing an arrow from LHS to RHS
@@ -332,6 +391,35 @@ where
             }
             old_len = new_len;
         }
+        //Rules anchored to the end finish the string, so unlike the rest they are never extended by more symbols
+        for (anchor, lhs, rhs) in &self.get_ruleset().anchored_rules {
+            if !anchor.at_end() {
+                continue;
+            }
+            for origin in 0..possible_dfa.state_transitions.len() {
+                if anchor.at_start() && origin != possible_dfa.starting_state {
+                    continue;
+                }
+                let parent = lhs.iter().fold(origin, |state, sym| {
+                    possible_dfa.state_transitions[state][*sym as usize]
+                });
+                let child = rhs.iter().fold(origin, |state, sym| {
+                    possible_dfa.state_transitions[state][*sym as usize]
+                });
+                rule_graph.update_edge(
+                    NodeIndex::new(parent),
+                    NodeIndex::new(child),
+                    RuleGraphRoot::new(
+                        lhs.clone(),
+                        rhs.clone(),
+                        parent,
+                        child,
+                        origin,
+                        &possible_dfa.symbol_set,
+                    ),
+                );
+            }
+        }
         rule_graph
     }
     fn is_superset<'a>(
@@ -356,7 +444,7 @@ where
         Ok(())
     }
     fn build_no_rule_dfa(&self) -> DFA {
-        if self.get_ruleset().rules.contains_key(&vec![]) {
+        let unanchored = if self.get_ruleset().rules.contains_key(&vec![]) {
             DFA {
                 accepting_states: Vec::new(),
                 starting_state: 0,
@@ -431,7 +519,22 @@ where
                 state_transitions: state_transitions,
                 symbol_set: self.get_goal().symbol_set.clone(),
             }
+        };
+        if self.get_ruleset().anchored_rules.is_empty() {
+            return unanchored;
         }
+        //A string is only terminal if no anchored rule applies to it either
+        let anchored_matches: Vec<_> = self
+            .get_ruleset()
+            .anchored_rules
+            .iter()
+            .map(|(anchor, lhs, _)| anchored_match_dfa(anchor, lhs, &self.get_goal().symbol_set))
+            .collect();
+        let mut dfas = vec![&unanchored];
+        dfas.extend(anchored_matches.iter());
+        DFA::product_many(&dfas, |outputs| {
+            *outputs[0] && outputs[1..].iter().all(|x| !**x)
+        })
     }
     fn build_path_graph(&self, possible_dfa: &DFA) -> Vec<Vec<Path>> {
         let hackLambda = |list: &Vec<usize>| {
@@ -451,12 +554,33 @@ where
         let mut old_paths = vec![];
         let mut paths = vec![vec![]; possible_dfa.state_transitions.len()];
         let mut iteration_num = 0;
-        paths[possible_dfa.starting_state].push(Path {
+        let anchored_rules = &self.get_ruleset().anchored_rules;
+        //How many of the last symbols to keep for matching rules anchored only to the end
+        let tail_len = anchored_rules
+            .iter()
+            .filter(|(anchor, _, _)| *anchor == Anchor::End)
+            .map(|(_, lhs, _)| lhs.len())
+            .max()
+            .unwrap_or(0);
+        let mut start_path = Path {
             buffer: vec![],
             rhs_connections: vec![],
             buffer_origin: possible_dfa.starting_state,
             goal_state: self.get_goal().starting_state,
-        });
+            start_prefix: if anchored_rules
+                .iter()
+                .any(|(anchor, _, _)| anchor.at_start())
+            {
+                Some(vec![])
+            } else {
+                None
+            },
+            tail: vec![],
+            tail_origin: possible_dfa.starting_state,
+            end_connections: vec![],
+        };
+        self.add_anchored_connections(&mut start_path, possible_dfa);
+        paths[possible_dfa.starting_state].push(start_path);
         while !new_paths.is_empty() {
             std::mem::swap(&mut old_paths, &mut new_paths);
             new_paths.clear();
@@ -490,11 +614,28 @@ where
                     let new_goal_state = self.get_goal().state_transitions
                         [paths[old_path.0][old_path.1].goal_state][symbol];
 
+                    let old_start_prefix = &paths[old_path.0][old_path.1].start_prefix;
+                    let mut new_tail = paths[old_path.0][old_path.1].tail.clone();
+                    let mut new_tail_origin = paths[old_path.0][old_path.1].tail_origin;
+                    new_tail.push(symbol as SymbolIdx);
+                    if new_tail.len() > tail_len {
+                        new_tail_origin = possible_dfa.state_transitions[new_tail_origin]
+                            [new_tail.remove(0) as usize];
+                    }
+
                     let mut new_path = Path {
                         buffer: new_buffer,
                         rhs_connections: vec![],
                         buffer_origin: paths[old_path.0][old_path.1].buffer_origin,
                         goal_state: new_goal_state,
+                        start_prefix: old_start_prefix.as_ref().map(|prefix| {
+                            let mut prefix = prefix.clone();
+                            prefix.push(symbol as SymbolIdx);
+                            prefix
+                        }),
+                        tail: new_tail,
+                        tail_origin: new_tail_origin,
+                        end_connections: vec![],
                     };
 
                     //Find where this path would be added
@@ -573,6 +714,17 @@ where
                             None => {}
                         }
                     }
+                    self.add_anchored_connections(&mut new_path, possible_dfa);
+                    //Once the string is no longer the start of any start-anchored LHS, stop tracking it
+                    if let Some(prefix) = &new_path.start_prefix {
+                        if !anchored_rules.iter().any(|(anchor, lhs, _)| {
+                            anchor.at_start()
+                                && lhs.len() > prefix.len()
+                                && lhs[..prefix.len()] == prefix[..]
+                        }) {
+                            new_path.start_prefix = None;
+                        }
+                    }
                     //We strip the buffer of any characters that we know will not be used as lhs
                     //I.e. if buffer is 2,0,2 we know that first 2 is never used, so buffer should become 0,2
                     let mut match_found = false;
@@ -614,14 +766,61 @@ where
                 }
             }
         }
+        //Connections through end-anchored rules only hold where the string ends,
+        //so they join the rest once no more symbols are being added
+        for path in paths.iter_mut().flatten() {
+            for connection in std::mem::take(&mut path.end_connections) {
+                if !path.rhs_connections.contains(&connection) {
+                    path.rhs_connections.push(connection);
+                }
+            }
+            path.rhs_connections.sort();
+        }
         paths
     }
+    //Adds the states reached by applying an anchored rule to the string a path has just read
+    fn add_anchored_connections(&self, path: &mut Path, possible_dfa: &DFA) {
+        for (anchor, lhs, rhs) in &self.get_ruleset().anchored_rules {
+            let origin = if anchor.at_start() {
+                if path.start_prefix.as_ref() != Some(lhs) {
+                    continue;
+                }
+                possible_dfa.starting_state
+            } else {
+                if !path.tail.ends_with(lhs) {
+                    continue;
+                }
+                path.tail[..path.tail.len() - lhs.len()]
+                    .iter()
+                    .fold(path.tail_origin, |state, sym| {
+                        possible_dfa.state_transitions[state][*sym as usize]
+                    })
+            };
+            let rhs_end_idx = rhs.iter().fold(origin, |state, sym| {
+                possible_dfa.state_transitions[state][*sym as usize]
+            });
+            let connections = if anchor.at_end() {
+                &mut path.end_connections
+            } else {
+                &mut path.rhs_connections
+            };
+            if !connections.contains(&rhs_end_idx) {
+                connections.push(rhs_end_idx);
+                connections.sort();
+            }
+        }
+    }
     //I'm hoping to give more nuanced proofs/proof failures soon
     //An audit trail (maybe integrated with that massive excel sheet I made) would be ideal
     fn is_correct(&self, possible_dfa: &DFA) -> bool {
         let no_rule_dfa = self.build_no_rule_dfa();
         //If the set of terminal strings is not correct in the possible_dfa
-        if &no_rule_dfa & self.get_goal() != &no_rule_dfa & possible_dfa {
+        //(i.e. some terminal string is reachable where the goal and possible_dfa disagree)
+        let terminal_mismatch =
+            DFA::product_many(&[&no_rule_dfa, self.get_goal(), possible_dfa], |outputs| {
+                *outputs[0] && outputs[1] != outputs[2]
+            });
+        if terminal_mismatch.accepting_states.iter().any(|x| *x) {
             //Throw the whole thing out!
             return false;
         }
@@ -1204,7 +1403,14 @@ impl<'a> std::fmt::Display for RuleGraphRoot<'a> {
 pub enum DomainError {
     Generating((Vec<SymbolIdx>, Vec<SymbolIdx>)),
     Deleting((Vec<SymbolIdx>, Vec<SymbolIdx>)),
-    Cyclic((Vec<SymbolIdx>, Vec<SymbolIdx>)),
+    //A string that can be rewritten back into itself -- each element rewrites to the next, and the last to the first
+    Cyclic(Vec<Vec<SymbolIdx>>),
+    //Needs more symbols than SymbolIdx can hold, e.g. lifting to a board with too many rows. Holds how many
+    TooManySymbols(usize),
+    //A goal without states, so there isn't a value to give any string
+    EmptyGoal,
+    //Neither a termination proof nor a cycle could be found for the rules
+    UnknownTermination,
 }
 
 impl DomainError {
@@ -1221,20 +1427,78 @@ impl DomainError {
                 symset.symbols_to_string(&lhs),
                 symset.symbols_to_string(&rhs)
             )),
-            DomainError::Cyclic((lhs, rhs)) => result.push_str(&format!(
-                "cyclic rules. Rules \"{0} - {1}\" and \"{1} - {0}\" create a cycle.",
-                symset.symbols_to_string(&lhs),
-                symset.symbols_to_string(&rhs)
+            DomainError::Cyclic(cycle) => result.push_str(&format!(
+                "cyclic rules. {} can be rewritten back into itself: {} -> {}.",
+                symset.symbols_to_string(&cycle[0]),
+                cycle
+                    .iter()
+                    .map(|x| symset.symbols_to_string(x))
+                    .collect::<Vec<_>>()
+                    .join(" -> "),
+                symset.symbols_to_string(&cycle[0])
             )),
+            DomainError::TooManySymbols(count) => result.push_str(&format!(
+                "{} symbols, at most {} are supported{}.",
+                count,
+                MAX_SYMBOLS,
+                if cfg!(feature = "wide_symbols") {
+                    ""
+                } else {
+                    " (build with the wide_symbols feature for more)"
+                }
+            )),
+            DomainError::EmptyGoal => result.push_str("goals with no states."),
+            DomainError::UnknownTermination => result.push_str(
+                "rules that can't be shown to terminate. No proof or cycle was found for the SRS.",
+            ),
         }
         result
     }
 }
 
+//Accepts the strings an anchored rule with this LHS can be applied to
+fn anchored_match_dfa(anchor: &Anchor, lhs: &[SymbolIdx], symbol_set: &SymbolSet) -> DFA {
+    let dead = lhs.len() + 1;
+    let mut state_transitions = vec![vec![dead; symbol_set.length]; lhs.len() + 2];
+    for (idx, sym) in lhs.iter().enumerate() {
+        state_transitions[idx][*sym as usize] = idx + 1;
+    }
+    //Anything may follow an LHS that only has to be at the start
+    if *anchor == Anchor::Start {
+        state_transitions[lhs.len()] = vec![lhs.len(); symbol_set.length];
+    }
+    let mut accepting_states = vec![false; lhs.len() + 2];
+    accepting_states[lhs.len()] = true;
+    let matches = DFA {
+        starting_state: 0,
+        state_transitions,
+        accepting_states,
+        symbol_set: symbol_set.clone(),
+    };
+    if anchor.at_start() {
+        matches
+    } else {
+        let anything = DFA {
+            starting_state: 0,
+            state_transitions: vec![vec![0; symbol_set.length]],
+            accepting_states: vec![true],
+            symbol_set: symbol_set.clone(),
+        };
+        anything.concat(&matches)
+    }
+}
+
 #[derive(PartialEq, Eq, Clone)]
 pub struct Path {
     buffer: Vec<SymbolIdx>,
     rhs_connections: Vec<usize>,
     buffer_origin: usize,
     goal_state: usize,
+    //The whole string so far, while it could still grow into the LHS of a start-anchored rule
+    start_prefix: Option<Vec<SymbolIdx>>,
+    //The last few symbols, for end-anchored rules, and the state before them
+    tail: Vec<SymbolIdx>,
+    tail_origin: usize,
+    //States reached by an end-anchored rule, which only hold if the string ends here
+    end_connections: Vec<usize>,
 }
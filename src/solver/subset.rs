@@ -5,7 +5,10 @@ use petgraph::{algo::toposort, graph::NodeIndex, prelude::DiGraph, Direction};
 
 use crate::{
     solver::{DFAStructure, SSStructure},
-    util::{Ruleset, SymbolIdx, DFA},
+    util::{
+        termination::{self, Termination},
+        Anchor, Ruleset, SymbolIdx, Symmetry, DFA,
+    },
 };
 
 use super::{srssolver::DomainError, Instant, SRSSolver, Solver};
@@ -20,6 +23,10 @@ pub struct SubsetSolver {
     sig_sets: Vec<BitVec>,
     solved_yet: Vec<BitVec>,
     unique_sigs: HashMap<BitVec, usize>,
+    //Symmetries of the rules and goal used to skip work. Empty unless set with with_symmetries
+    symmetries: Vec<Symmetry>,
+    //For each symmetry, the index of the image of each signature set element
+    sig_images: Vec<Vec<usize>>,
 }
 
 impl SRSSolver for SubsetSolver {
@@ -36,8 +43,11 @@ impl SRSSolver for SubsetSolver {
             }
         }
 
-        if let Some(problem) = ruleset.has_definitely_cyclic_rule() {
-            return Err(DomainError::Cyclic(problem));
+        //Cycles would send the solver into infinite recursion, so rulesets that can't be proven either way are refused too
+        match termination::check_termination(&ruleset) {
+            Termination::Terminating(_) => {}
+            Termination::Cycle(cycle) => return Err(DomainError::Cyclic(cycle)),
+            Termination::Unknown => return Err(DomainError::UnknownTermination),
         }
         Self::ensure_expansion(&mut ruleset, &mut goal);
 
@@ -51,6 +61,8 @@ impl SRSSolver for SubsetSolver {
             min_input: min_input,
             max_input: max_input,
             unique_sigs: HashMap::new(),
+            symmetries: vec![],
+            sig_images: vec![],
         })
     }
 
@@ -67,11 +79,11 @@ impl Solver for SubsetSolver {
         "Clean up",
     ];
 
-    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<u8>) -> bool {
+    fn evaluate<'a, 'b>(&'a self, state: &'b Vec<SymbolIdx>) -> bool {
         todo!()
     }
 
-    fn mutate(&self, state: Vec<u8>, input: SymbolIdx) -> Vec<u8> {
+    fn mutate(&self, state: Vec<SymbolIdx>, input: SymbolIdx) -> Vec<SymbolIdx> {
         todo!()
     }
 
@@ -88,10 +100,24 @@ impl Solver for SubsetSolver {
         //Usize is index in trans_table
 
         assert!(
-            core::cmp::PartialEq::<Vec<u8>>::eq(&origin, &vec![]),
+            core::cmp::PartialEq::<Vec<SymbolIdx>>::eq(&origin, &vec![]),
             "doesn't support non-null origin (sorry!)"
         );
         let sig_set = &self.rules.symbol_set.build_sig_k(sig_k);
+        self.sig_images = self
+            .symmetries
+            .iter()
+            .map(|symmetry| {
+                sig_set
+                    .iter()
+                    .map(|x| {
+                        self.rules
+                            .symbol_set
+                            .find_in_sig_set(symmetry.apply(x).iter())
+                    })
+                    .collect()
+            })
+            .collect();
 
         //not allowed to complain about my dumb code -- not everything will be optimal i have DEADLINES.
         //okay i'm the one making up the deadlines... but still
@@ -150,8 +176,23 @@ impl Solver for SubsetSolver {
                 link_graph.add_node(index);
             }
             let mut old_len = link_graph.edge_count();
+            //Links are extended by every symbol afterwards, which rules anchored to the end can't be.
+            //Those are left to bfs_solver_sub, and rules anchored to the start only link from the starting state
+            let start_rules: Vec<_> = self
+                .rules
+                .anchored_rules
+                .iter()
+                .filter(|(anchor, _, _)| *anchor == Anchor::Start)
+                .map(|(_, lhs, rhs)| (lhs, vec![rhs.clone()]))
+                .collect();
             for origin in 0..last_known {
-                for rule_list in &self.rules.rules {
+                let origin_rules = self.rules.rules.iter().chain(
+                    start_rules
+                        .iter()
+                        .filter(|_| origin == 0)
+                        .map(|(lhs, rhs)| (*lhs, rhs)),
+                );
+                for rule_list in origin_rules {
                     let lhs = rule_list.0;
                     for rhs in rule_list.1 {
                         let mut parent = origin;
@@ -203,7 +244,7 @@ impl Solver for SubsetSolver {
             for origin_idx in last_finished..last_known {
                 for (sym, move_idx) in self.trans_table[origin_idx].iter().enumerate() {
                     for elem in &smaller_sig {
-                        let mut elem_in_origin = vec![sym as u8];
+                        let mut elem_in_origin = vec![sym as SymbolIdx];
                         elem_in_origin.extend(elem.iter());
                         let old_idx = self.rules.symbol_set.find_in_sig_set(elem_in_origin.iter());
                         let new_idx = self.rules.symbol_set.find_in_sig_set(elem.iter());
@@ -267,6 +308,8 @@ impl Solver for SubsetSolver {
             //Unknown-unknown.
             let mut reverse_link_graph = link_graph.clone();
             reverse_link_graph.reverse();
+            //Prospective boards whose signature sets have been fully worked out this pass
+            let mut finished_boards: HashMap<Vec<SymbolIdx>, usize> = HashMap::new();
             for node in toposort(&reverse_link_graph, None).unwrap() {
                 if link_graph[node][0] >= last_known {
                     //Get info about what's false from all incoming neighbors
@@ -294,7 +337,35 @@ impl Solver for SubsetSolver {
                         as SymbolIdx;
                     let mut new_board = recent_strings[connecting_state].clone();
                     new_board.push(connecting_symbol);
-                    self.sig_with_set_sub(&new_board, &sig_set, link_graph[node][0]);
+                    //If a symmetry sends this board to a finished one, element x is solvable here
+                    //exactly when x's image is solvable there
+                    let finished_image =
+                        self.symmetries
+                            .iter()
+                            .enumerate()
+                            .find_map(|(symmetry_idx, symmetry)| {
+                                if symmetry.reversed {
+                                    return None;
+                                }
+                                finished_boards
+                                    .get(&symmetry.apply(&new_board))
+                                    .map(|x| (symmetry_idx, *x))
+                            });
+                    let state_idx = link_graph[node][0];
+                    match finished_image {
+                        Some((symmetry_idx, image_idx)) => {
+                            for idx in 0..sig_set.len() {
+                                let solvable =
+                                    self.sig_sets[image_idx][self.sig_images[symmetry_idx][idx]];
+                                self.sig_sets[state_idx].set(idx, solvable);
+                            }
+                            self.solved_yet[state_idx - last_known].fill(true);
+                        }
+                        None => {
+                            self.sig_with_set_sub(&new_board, &sig_set, state_idx);
+                            finished_boards.insert(new_board, state_idx);
+                        }
+                    }
                 }
             }
 
@@ -380,9 +451,10 @@ impl Solver for SubsetSolver {
                 phase_events.send(dur).unwrap();
             }
         }
-        let mut accepting_states = Vec::new();
+        //unique_sigs isn't in state order, so each state's entry goes where it belongs
+        let mut accepting_states = vec![false; self.unique_sigs.len()];
         for (key, val) in self.unique_sigs.iter() {
-            accepting_states.push(key[0])
+            accepting_states[*val] = key[0];
         }
         let trans_table = self.trans_table.clone();
         if is_debug {
@@ -408,6 +480,13 @@ impl Solver for SubsetSolver {
 }
 
 impl SubsetSolver {
+    //Lets runs skip working out strings that a symmetry sends to ones already worked out.
+    //Every symmetry given has to actually be one (see Symmetry::find_all), or the result will be wrong
+    pub fn with_symmetries(mut self, symmetries: Vec<Symmetry>) -> Self {
+        self.symmetries = symmetries;
+        self
+    }
+
     fn bfs_solver_sub(
         &mut self,
         start_board: &Vec<SymbolIdx>,
@@ -464,12 +543,29 @@ impl SubsetSolver {
     ) {
         let solved_idx = state_idx - self.trans_table.len();
         let mut investigated = HashSet::new();
+        //Symmetries that send the board to itself send each element to one with the same answer.
+        //Reversed ones only do when there is no board for the element to end up in front of
+        let fixing: Vec<usize> = (0..self.symmetries.len())
+            .filter(|x| {
+                let symmetry = &self.symmetries[*x];
+                if symmetry.reversed {
+                    board.is_empty()
+                } else {
+                    symmetry.apply(board) == *board
+                }
+            })
+            .collect();
         for (idx, sig_element) in sig_set.iter().enumerate() {
             if !self.solved_yet[solved_idx][idx] {
                 let mut new_board = board.clone();
                 new_board.extend(sig_element);
-                self.bfs_solver_sub(&new_board, state_idx, idx, &mut investigated);
+                let solvable = self.bfs_solver_sub(&new_board, state_idx, idx, &mut investigated);
                 investigated.clear();
+                for symmetry_idx in &fixing {
+                    let image = self.sig_images[*symmetry_idx][idx];
+                    self.solved_yet[solved_idx].set(image, true);
+                    self.sig_sets[state_idx].set(image, solvable);
+                }
             }
         }
     }
@@ -0,0 +1,254 @@
+//Compact binary DFA files, for results too big to load quickly as JSON.
+//Layout, all integers little-endian u32:
+//  magic "SDFA", format version, state count, symbol count, starting state
+//  symbol table: each symbol's name as its length in bytes followed by its UTF-8
+//  padding up to a multiple of 4 bytes
+//  transitions: state count * symbol count targets, state by state
+//  accepting states: one bit per state, lowest bit of each byte first
+//DFAView reads straight from the file's bytes, so a memory-mapped file can be queried without loading it
+use std::fmt;
+
+use super::{SymbolIdx, SymbolSet, DFA, MAX_SYMBOLS};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs::File, io::Write, path::Path};
+
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+
+const MAGIC: &[u8; 4] = b"SDFA";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryDFAError {
+    //Doesn't start with the magic bytes, so isn't a binary DFA file
+    NotBinaryDFA,
+    //Written in a version of the format this doesn't know
+    UnsupportedVersion(u32),
+    //The file ends before everything its header promises, or goes on after it
+    WrongLength,
+    //More symbols than SymbolIdx can index
+    TooManySymbols(usize),
+    //The symbol with this index isn't valid UTF-8
+    InvalidSymbol(usize),
+    //The starting state or a transition goes to a state that doesn't exist
+    StateOutOfRange(usize),
+    //The file couldn't be read
+    Io(String),
+}
+
+impl fmt::Display for BinaryDFAError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryDFAError::NotBinaryDFA => write!(f, "not a binary DFA file"),
+            BinaryDFAError::UnsupportedVersion(version) => {
+                write!(f, "unsupported binary DFA format version {}", version)
+            }
+            BinaryDFAError::WrongLength => {
+                write!(f, "file length doesn't match the size in its header")
+            }
+            BinaryDFAError::TooManySymbols(count) => write!(
+                f,
+                "{} symbols, at most {} are supported",
+                count, MAX_SYMBOLS
+            ),
+            BinaryDFAError::InvalidSymbol(idx) => write!(f, "symbol {} isn't valid UTF-8", idx),
+            BinaryDFAError::StateOutOfRange(state) => {
+                write!(f, "state {} doesn't exist", state)
+            }
+            BinaryDFAError::Io(message) => write!(f, "could not read file: {}", message),
+        }
+    }
+}
+
+impl DFA {
+    pub fn to_binary(&self) -> Vec<u8> {
+        let as_u32 = |x: usize| u32::try_from(x).expect("DFA too big for the binary format");
+        let state_count = self.state_transitions.len();
+        let mut bytes = MAGIC.to_vec();
+        for field in [
+            FORMAT_VERSION,
+            as_u32(state_count),
+            as_u32(self.symbol_set.length),
+            as_u32(self.starting_state),
+        ] {
+            bytes.extend(field.to_le_bytes());
+        }
+        for symbol in &self.symbol_set.representations {
+            bytes.extend(as_u32(symbol.len()).to_le_bytes());
+            bytes.extend(symbol.as_bytes());
+        }
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+        for target in self.state_transitions.iter().flatten() {
+            bytes.extend(as_u32(*target).to_le_bytes());
+        }
+        let mut accepting = vec![0u8; state_count.div_ceil(8)];
+        for (state, _) in self.accepting_states.iter().enumerate().filter(|x| *x.1) {
+            accepting[state / 8] |= 1 << (state % 8);
+        }
+        bytes.extend(accepting);
+        bytes
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<DFA, BinaryDFAError> {
+        Ok(DFAView::new(bytes)?.to_dfa())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_binary(&self, file: &mut File) -> std::io::Result<()> {
+        file.write_all(&self.to_binary())
+    }
+}
+
+//A read-only DFA over the bytes of a binary DFA file, answering queries without deserialising it
+pub struct DFAView<B> {
+    bytes: B,
+    symbol_set: SymbolSet,
+    state_count: usize,
+    starting_state: usize,
+    transitions_start: usize,
+    accepting_start: usize,
+}
+
+impl<B> DFAView<B>
+where
+    B: AsRef<[u8]>,
+{
+    //Checks the whole file, so later queries can't run off the end of it or into missing states
+    pub fn new(bytes: B) -> Result<Self, BinaryDFAError> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(BinaryDFAError::NotBinaryDFA);
+        }
+        let version = read_u32(data, 4);
+        if version != FORMAT_VERSION {
+            return Err(BinaryDFAError::UnsupportedVersion(version));
+        }
+        let state_count = read_u32(data, 8) as usize;
+        let symbol_count = read_u32(data, 12) as usize;
+        let starting_state = read_u32(data, 16) as usize;
+        if symbol_count > MAX_SYMBOLS {
+            return Err(BinaryDFAError::TooManySymbols(symbol_count));
+        }
+
+        let mut cursor = HEADER_LEN;
+        let mut representations = Vec::with_capacity(symbol_count);
+        for idx in 0..symbol_count {
+            if data.len() < cursor + 4 {
+                return Err(BinaryDFAError::WrongLength);
+            }
+            let len = read_u32(data, cursor) as usize;
+            cursor += 4;
+            let name = data
+                .get(cursor..cursor + len)
+                .ok_or(BinaryDFAError::WrongLength)?;
+            let name = std::str::from_utf8(name).map_err(|_| BinaryDFAError::InvalidSymbol(idx))?;
+            representations.push(name.to_owned());
+            cursor += len;
+        }
+        let transitions_start = cursor.next_multiple_of(4);
+        let accepting_start = state_count
+            .checked_mul(symbol_count)
+            .and_then(|x| x.checked_mul(4))
+            .and_then(|x| x.checked_add(transitions_start))
+            .ok_or(BinaryDFAError::WrongLength)?;
+        if data.len() != accepting_start + state_count.div_ceil(8) {
+            return Err(BinaryDFAError::WrongLength);
+        }
+
+        if starting_state >= state_count {
+            return Err(BinaryDFAError::StateOutOfRange(starting_state));
+        }
+        if let Some(target) = (transitions_start..accepting_start)
+            .step_by(4)
+            .map(|x| read_u32(data, x) as usize)
+            .find(|x| *x >= state_count)
+        {
+            return Err(BinaryDFAError::StateOutOfRange(target));
+        }
+
+        Ok(DFAView {
+            bytes,
+            symbol_set: SymbolSet {
+                length: symbol_count,
+                representations,
+            },
+            state_count,
+            starting_state,
+            transitions_start,
+            accepting_start,
+        })
+    }
+
+    pub fn symbol_set(&self) -> &SymbolSet {
+        &self.symbol_set
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.state_count
+    }
+
+    pub fn starting_state(&self) -> usize {
+        self.starting_state
+    }
+
+    pub fn transition(&self, state: usize, symbol: SymbolIdx) -> usize {
+        //Out of range indices would quietly read some other state's row
+        assert!(state < self.state_count, "state {} doesn't exist", state);
+        assert!(
+            (symbol as usize) < self.symbol_set.length,
+            "symbol {} doesn't exist",
+            symbol
+        );
+        let idx = state * self.symbol_set.length + symbol as usize;
+        read_u32(self.bytes.as_ref(), self.transitions_start + 4 * idx) as usize
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.bytes.as_ref()[self.accepting_start + state / 8] >> (state % 8) & 1 == 1
+    }
+
+    pub fn final_state(&self, input: &[SymbolIdx]) -> usize {
+        input.iter().fold(self.starting_state, |state, symbol| {
+            self.transition(state, *symbol)
+        })
+    }
+
+    pub fn contains(&self, input: &[SymbolIdx]) -> bool {
+        self.is_accepting(self.final_state(input))
+    }
+
+    //Reads the whole thing into an ordinary DFA
+    pub fn to_dfa(&self) -> DFA {
+        DFA {
+            starting_state: self.starting_state,
+            state_transitions: (0..self.state_count)
+                .map(|state| {
+                    (0..self.symbol_set.length)
+                        .map(|symbol| self.transition(state, symbol as SymbolIdx))
+                        .collect()
+                })
+                .collect(),
+            accepting_states: (0..self.state_count)
+                .map(|state| self.is_accepting(state))
+                .collect(),
+            symbol_set: self.symbol_set.clone(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DFAView<Mmap> {
+    //Maps the file into memory rather than reading it. The file mustn't be changed while the view is open
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, BinaryDFAError> {
+        let file = File::open(path).map_err(|e| BinaryDFAError::Io(e.to_string()))?;
+        //Safe as long as nothing else modifies the file, as above
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| BinaryDFAError::Io(e.to_string()))?;
+        DFAView::new(map)
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+}
@@ -0,0 +1,141 @@
+//Counting the strings a DFA accepts without enumerating them.
+//Everything here works off the transition table, so lengths in the thousands are fine
+use std::collections::HashMap;
+
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+
+use super::{DFA, NFA};
+
+//Power iteration stops once the bounds on the spectral radius are this close, relative to its size
+const GROWTH_RATE_TOLERANCE: f64 = 1e-12;
+const GROWTH_RATE_MAX_ITERATIONS: usize = 100_000;
+
+impl DFA {
+    //How many strings of exactly this length are accepted
+    pub fn count_accepted(&self, length: usize) -> BigUint {
+        self.count_accepted_table(length).pop().unwrap()
+    }
+
+    //How many strings of each length from 0 to max_length (inclusive) are accepted
+    pub fn count_accepted_table(&self, max_length: usize) -> Vec<BigUint> {
+        //Number of strings of the current length ending in each state
+        let mut ways = vec![BigUint::default(); self.state_transitions.len()];
+        ways[self.starting_state] = BigUint::from(1u8);
+        let mut table = Vec::with_capacity(max_length + 1);
+        loop {
+            table.push(
+                ways.iter()
+                    .zip(&self.accepting_states)
+                    .filter(|x| *x.1)
+                    .map(|x| x.0)
+                    .sum(),
+            );
+            if table.len() > max_length {
+                return table;
+            }
+            let mut next = vec![BigUint::default(); self.state_transitions.len()];
+            for (state, transitions) in self.state_transitions.iter().enumerate() {
+                for target in transitions {
+                    next[*target] += &ways[state];
+                }
+            }
+            ways = next;
+        }
+    }
+
+    //The fraction of strings of this length that are accepted, exactly
+    pub fn accepted_ratio(&self, length: usize) -> BigRational {
+        let total = BigUint::from(self.symbol_set.length).pow(length as u32);
+        BigRational::new(
+            BigInt::from(self.count_accepted(length)),
+            BigInt::from(total),
+        )
+    }
+
+    //The base of the exponential growth in accepted strings per length: count_accepted(n) grows like
+    //growth_rate()^n, up to polynomial factors. This is the spectral radius of the transition matrix
+    //restricted to states that are reachable and can still accept.
+    //0 means finitely many strings are accepted, 1 polynomially many, and the number of symbols everything
+    pub fn growth_rate(&self) -> f64 {
+        //The radius of the whole matrix is the largest of its strongly connected components'
+        self.useful_components()
+            .iter()
+            .map(|component| component_radius(component.len(), &self.component_edges(component)))
+            .fold(0.0, f64::max)
+    }
+
+    //Whether only finitely many strings are accepted, i.e. no reachable cycle can still accept
+    pub fn is_finite(&self) -> bool {
+        self.useful_components()
+            .iter()
+            .all(|component| self.component_edges(component).is_empty())
+    }
+
+    //Strongly connected components of the states that are reachable and can still accept
+    fn useful_components(&self) -> Vec<Vec<usize>> {
+        let reachable = NFA::from(self).reachable();
+        let can_accept = NFA::from(self).reverse().reachable();
+        let mut graph = DiGraph::<(), ()>::new();
+        for _ in &self.state_transitions {
+            graph.add_node(());
+        }
+        for (state, transitions) in self.state_transitions.iter().enumerate() {
+            for target in transitions {
+                graph.add_edge(NodeIndex::new(state), NodeIndex::new(*target), ());
+            }
+        }
+        //Components are entirely useful or not at all, since their states reach each other
+        tarjan_scc(&graph)
+            .iter()
+            .map(|component| component.iter().map(|x| x.index()).collect::<Vec<_>>())
+            .filter(|component| reachable[component[0]] && can_accept[component[0]])
+            .collect()
+    }
+
+    //Each transition within a component, once per symbol taking it, by position in the component
+    fn component_edges(&self, states: &[usize]) -> Vec<(usize, usize)> {
+        let positions: HashMap<usize, usize> =
+            states.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        states
+            .iter()
+            .enumerate()
+            .flat_map(|(i, state)| {
+                self.state_transitions[*state]
+                    .iter()
+                    .filter_map(|target| positions.get(target).map(|j| (i, *j)))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+//Spectral radius of a strongly connected component's transitions.
+//Iterates with the identity added, which keeps periodic components from oscillating,
+//and brackets the radius between the smallest and largest growth of any entry (Collatz-Wielandt)
+fn component_radius(size: usize, edges: &[(usize, usize)]) -> f64 {
+    if edges.is_empty() {
+        return 0.0;
+    }
+    let mut vector = vec![1.0; size];
+    let mut bounds = (0.0, f64::MAX);
+    for _ in 0..GROWTH_RATE_MAX_ITERATIONS {
+        let mut next = vector.clone();
+        for (from, to) in edges {
+            next[*from] += vector[*to];
+        }
+        bounds = next
+            .iter()
+            .zip(&vector)
+            .map(|(a, b)| a / b)
+            .fold((f64::MAX, 0.0), |x, ratio| (x.0.min(ratio), x.1.max(ratio)));
+        if bounds.1 - bounds.0 <= GROWTH_RATE_TOLERANCE * bounds.1 {
+            break;
+        }
+        let largest = next.iter().cloned().fold(0.0, f64::max);
+        vector = next.iter().map(|x| x / largest).collect();
+    }
+    (bounds.0 + bounds.1) / 2.0 - 1.0
+}
@@ -1,22 +1,19 @@
+use super::search;
+use super::validate::UncheckedDFA;
 use crate::{SymbolIdx, SymbolSet};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    io::Read,
     marker::PhantomData,
     ops::{Deref, IndexMut},
     slice::SliceIndex,
 };
-use xml::{
-    reader::EventReader,
-    writer::{EmitterConfig, XmlEvent},
-};
 
 use bitvec::prelude::*;
 use serde_json::{value::Index, Result};
-use std::io::Write;
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedDFA<Input, Output>")]
 pub struct DFA<Input = String, Output = bool> {
     pub starting_state: usize,
     pub state_transitions: Vec<Vec<usize>>,
@@ -40,12 +37,6 @@ impl GetDiscriminant for bool {
     }
     const DISCRIMINANT_LEN: usize = 2;
 }
-enum JFLAPTrans {
-    From,
-    To,
-    Read,
-    Unknown,
-}
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
@@ -112,11 +103,7 @@ where
     type Output = DFA<I, O>;
     fn not(self) -> Self::Output {
         let mut clone = self.clone();
-        let mut inverted_accepting_states = Vec::new();
-        for i in 0..self.state_transitions.len() {
-            inverted_accepting_states[i] = !self.accepting_states[i].clone();
-        }
-        clone.accepting_states = inverted_accepting_states;
+        clone.accepting_states = self.accepting_states.iter().map(|x| !x.clone()).collect();
         clone
     }
 }
@@ -184,223 +171,57 @@ impl DFA {
             expanded_idx += 1;
         }
 
-        //Find/create an error state that all of the new transitions will be routed to
-        let mut error_state = None;
-        for i in 0..self.state_transitions.len() {
-            if self.accepting_states[i] {
-                continue;
-            }
-            if self.state_transitions[i].iter().all(|x| x == &i) {
-                error_state = Some(i);
-            }
-        }
-        let error_state = match error_state {
-            Some(s) => s,
-            None => {
-                self.state_transitions
-                    .push(vec![self.state_transitions.len(); self.symbol_set.length]);
-                self.state_transitions.len() - 1
-            }
-        };
-
-        //Modify transition table to include
+        //Route the new transitions to an error state, found or created once the table is built
         for i in 0..self.state_transitions.len() {
             let mut new_trans = Vec::with_capacity(expanded_ss.length);
             let mut holes_encountered = 0;
             for j in 0..self.state_transitions[i].len() {
                 while holes_encountered < holes.len() && holes[holes_encountered] == j {
-                    new_trans.push(error_state);
+                    new_trans.push(usize::MAX);
                     holes_encountered += 1;
                 }
                 new_trans.push(self.state_transitions[i][j]);
             }
             while holes_encountered < holes.len() {
-                new_trans.push(error_state);
+                new_trans.push(usize::MAX);
                 holes_encountered += 1;
             }
             self.state_transitions[i] = new_trans;
         }
         self.symbol_set = expanded_ss;
+        self.complete_with_sink();
     }
-
-    pub fn load_jflap_from_string(input_xml: &str) -> Self {
-        let mut trans_table;
-        let mut accepting_states = vec![];
-        let mut num_states = 0;
-        let mut starting_state = 0;
-        let mut e_reader = EventReader::from_str(input_xml);
-        let mut cur_state = 0;
-        let mut trans_vec = vec![];
-        let mut unique_reps = HashSet::new();
-        let mut jflap_trans = JFLAPTrans::Unknown;
-
-        let mut state_ids: HashMap<String, usize> = HashMap::new();
-
-        while let Ok(cur_event) = e_reader.next() {
-            match cur_event {
-                xml::reader::XmlEvent::EndDocument => break,
-                xml::reader::XmlEvent::Whitespace(_) => {}
-                xml::reader::XmlEvent::StartDocument {
-                    version: _,
-                    encoding: _,
-                    standalone: _,
-                } => {}
-                xml::reader::XmlEvent::Comment(_) => {}
-                xml::reader::XmlEvent::StartElement {
-                    name,
-                    attributes,
-                    namespace: _,
-                } => match &name.local_name[..] {
-                    "state" => {
-                        let cur_str = &attributes
-                            .iter()
-                            .find(|&x| x.name.local_name == "id")
-                            .unwrap()
-                            .value;
-                        cur_state = cur_str.parse().unwrap();
-                        state_ids.insert(cur_str.clone(), num_states);
-                        num_states += 1;
-                    }
-                    "initial" => starting_state = cur_state,
-                    "final" => {
-                        accepting_states[num_states - 1] = true;
-                    }
-                    "transition" => trans_vec.push((0, 0, "".to_owned())),
-                    "from" => jflap_trans = JFLAPTrans::From,
-                    "to" => jflap_trans = JFLAPTrans::To,
-                    "read" => jflap_trans = JFLAPTrans::Read,
-                    _ => {}
-                },
-                xml::reader::XmlEvent::EndElement { name } => match &name.local_name[..] {
-                    "from" | "to" | "read" => jflap_trans = JFLAPTrans::Unknown,
-                    _ => {}
-                },
-                xml::reader::XmlEvent::Characters(chars) => match jflap_trans {
-                    JFLAPTrans::From => {
-                        trans_vec.last_mut().unwrap().0 = *state_ids.get(&chars).unwrap()
-                    }
-                    JFLAPTrans::To => {
-                        trans_vec.last_mut().unwrap().1 = *state_ids.get(&chars).unwrap()
-                    }
-                    JFLAPTrans::Read => {
-                        trans_vec.last_mut().unwrap().2 = chars.clone();
-                        unique_reps.insert(chars);
-                    }
-                    JFLAPTrans::Unknown => {}
-                },
-                _ => {}
-            }
-        }
-        let mut reps_vec: Vec<String> = unique_reps.into_iter().collect();
-        reps_vec.sort();
-
-        trans_table = vec![vec![usize::MAX; reps_vec.len()]; num_states];
-
-        for transition in &trans_vec {
-            trans_table[transition.0][reps_vec.iter().position(|x| x == &transition.2).unwrap()] =
-                transition.1;
-        }
-        //If dfa is incomplete
-        if trans_vec.len() < reps_vec.len() * trans_table.len() {
-            let mut error_state_already = None;
-            for state_idx in 0..trans_table.len() {
-                if !accepting_states[state_idx]
-                    && trans_table[state_idx]
-                        .iter()
-                        .all(|f| f == &state_idx || f == &usize::MAX)
-                {
-                    error_state_already = Some(state_idx);
-                }
-            }
-            let error_state = match error_state_already {
-                Some(e_state) => e_state,
-                None => {
-                    trans_table.push(vec![trans_table.len(); reps_vec.len()]);
-                    trans_table.len() - 1
-                }
-            };
-            for state_trans in &mut trans_table {
-                state_trans.iter_mut().for_each(|f| {
-                    if *f == usize::MAX {
-                        *f = error_state
-                    }
-                });
-            }
-        }
-
-        let temp = SymbolSet {
-            length: reps_vec.len(),
-            representations: reps_vec,
-        };
-        DFA {
-            starting_state: starting_state,
-            state_transitions: trans_table,
-            accepting_states: accepting_states,
-            symbol_set: temp,
+}
+//Running strings through only needs outputs to be cloneable, so works on Moore machines with any outputs
+impl<I, O> DFA<I, O>
+where
+    O: Clone,
+{
+    pub fn contains(&self, input: &Vec<SymbolIdx>) -> O {
+        let mut state = self.starting_state;
+        for i in input {
+            state = self.state_transitions[state][*i as usize];
         }
+        self.accepting_states[state].clone()
     }
 
-    pub fn save_jflap_to_bytes(&self) -> Vec<u8> {
-        let mut output_str = vec![];
-        let mut w = EmitterConfig::new()
-            .perform_indent(true)
-            .create_writer(&mut output_str);
-        w.write(XmlEvent::start_element("structure")).unwrap();
-        w.write(XmlEvent::start_element("type")).unwrap();
-        w.write(XmlEvent::characters("fa")).unwrap();
-        w.write(XmlEvent::end_element()).unwrap();
-        w.write(XmlEvent::start_element("automaton")).unwrap();
-
-        for idx in 0..self.state_transitions.len() {
-            w.write(
-                XmlEvent::start_element("state")
-                    .attr("id", &idx.to_string())
-                    .attr("name", &("q".to_owned() + &idx.to_string())),
-            )
-            .unwrap();
-            if idx == self.starting_state {
-                w.write(XmlEvent::start_element("initial")).unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-            }
-            if self.accepting_states[idx] {
-                w.write(XmlEvent::start_element("final")).unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-            }
-            w.write(XmlEvent::end_element()).unwrap();
-        }
-        let symbols = &self.symbol_set.representations;
-        for (idx, state) in self.state_transitions.iter().enumerate() {
-            for (idx2, target) in state.iter().enumerate() {
-                w.write(XmlEvent::start_element("transition")).unwrap();
-                w.write(XmlEvent::start_element("from")).unwrap();
-                w.write(XmlEvent::characters(&idx.to_string())).unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-                w.write(XmlEvent::start_element("to")).unwrap();
-                w.write(XmlEvent::characters(&target.to_string())).unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-                w.write(XmlEvent::start_element("read")).unwrap();
-                w.write(XmlEvent::characters(&format!("{}", symbols[idx2])))
-                    .unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-                w.write(XmlEvent::end_element()).unwrap();
-            }
+    pub fn final_state(&self, input: &Vec<SymbolIdx>) -> usize {
+        let mut state = self.starting_state;
+        for i in input {
+            state = self.state_transitions[state][*i as usize];
         }
-        w.write(XmlEvent::end_element()).unwrap();
-        w.write(XmlEvent::end_element()).unwrap();
-        output_str
+        state
     }
 
-    pub fn jflap_save(&self, file: &mut File) {
-        //let mut file = fs::File::create(filename.clone().to_owned() + ".jff").unwrap();
-        let _ = file.write(&self.save_jflap_to_bytes());
-    }
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn jflap_load(file: &mut File) -> Self {
-        let mut contents = "".to_owned();
-        file.read_to_string(&mut contents).unwrap();
-        Self::load_jflap_from_string(&contents)
+    pub fn contains_from_start(&self, input: &Vec<SymbolIdx>, start: usize) -> O {
+        let mut state = start;
+        for i in input {
+            state = self.state_transitions[state][*i as usize];
+        }
+        self.accepting_states[state].clone()
     }
 }
+
 impl<I, O> DFA<I, O>
 where
     I: Clone,
@@ -411,6 +232,7 @@ where
         F: Fn(&O, &O) -> O,
     {
         let mut stored_idxs = vec![(self.starting_state, other.starting_state)];
+        let mut pair_idxs = HashMap::from([(stored_idxs[0], 0)]);
         let mut transition_table = vec![];
         let mut accepting_states = vec![];
         accepting_states.push(accepting_rule(
@@ -425,9 +247,9 @@ where
                     let new_other_idx =
                         other.state_transitions[stored_idxs[new_state_idx].1][symbol];
                     let new_pair = (new_self_idx, new_other_idx);
-                    match stored_idxs.iter().position(|f| f == &new_pair) {
+                    match pair_idxs.get(&new_pair) {
                         Some(pos) => {
-                            transition_table.last_mut().unwrap()[symbol] = pos;
+                            transition_table.last_mut().unwrap()[symbol] = *pos;
                         }
                         None => {
                             accepting_states.push(accepting_rule(
@@ -435,6 +257,7 @@ where
                                 &other.accepting_states[new_pair.1],
                             ));
                             transition_table.last_mut().unwrap()[symbol] = stored_idxs.len();
+                            pair_idxs.insert(new_pair, stored_idxs.len());
                             stored_idxs.push(new_pair);
                         }
                     }
@@ -449,82 +272,243 @@ where
         }
     }
 
+    //Runs every DFA side by side, with combine deciding the output of each tuple of states (in the order given).
+    //All of the DFAs must share a symbol set
+    pub fn product_many<F, P>(dfas: &[&DFA<I, O>], combine: F) -> DFA<I, P>
+    where
+        F: Fn(&[&O]) -> P,
+    {
+        assert!(!dfas.is_empty(), "product of no DFAs");
+        let symbol_count = dfas[0].symbol_set.length;
+        assert!(
+            dfas.iter().all(|x| x.symbol_set.length == symbol_count),
+            "DFAs in a product must share a symbol set"
+        );
+        let output = |tuple: &[usize]| {
+            let outputs: Vec<&O> = dfas
+                .iter()
+                .zip(tuple)
+                .map(|(dfa, state)| &dfa.accepting_states[*state])
+                .collect();
+            combine(&outputs)
+        };
+        let start: Vec<usize> = dfas.iter().map(|x| x.starting_state).collect();
+        let mut accepting_states = vec![output(&start)];
+        let mut tuple_idxs = HashMap::from([(start.clone(), 0)]);
+        let mut stored_tuples = vec![start];
+        let mut transition_table = vec![];
+        while transition_table.len() < stored_tuples.len() {
+            let mut transitions = vec![0; symbol_count];
+            for (symbol, target) in transitions.iter_mut().enumerate() {
+                let next: Vec<usize> = dfas
+                    .iter()
+                    .zip(&stored_tuples[transition_table.len()])
+                    .map(|(dfa, state)| dfa.state_transitions[*state][symbol])
+                    .collect();
+                *target = match tuple_idxs.get(&next) {
+                    Some(idx) => *idx,
+                    None => {
+                        accepting_states.push(output(&next));
+                        tuple_idxs.insert(next.clone(), stored_tuples.len());
+                        stored_tuples.push(next);
+                        stored_tuples.len() - 1
+                    }
+                };
+            }
+            transition_table.push(transitions);
+        }
+        DFA {
+            starting_state: 0,
+            state_transitions: transition_table,
+            accepting_states,
+            symbol_set: dfas[0].symbol_set.clone(),
+        }
+    }
+
+    //Same as product_many, but merges equivalent states as it goes. The DFAs are folded in one at a time, and each
+    //partial product (keeping every output so far) is minimised before the next DFA joins it, so the full product
+    //of all of them is only ever built when it's already minimal
+    pub fn product_many_minimized<F, P>(dfas: &[&DFA<I, O>], combine: F) -> DFA<I, P>
+    where
+        F: Fn(&[&O]) -> P,
+        P: Clone + Ord,
+    {
+        assert!(!dfas.is_empty(), "product of no DFAs");
+        let minimized_tuples = |dfa: &DFA<I, O>| {
+            let mut tuples = DFA {
+                starting_state: dfa.starting_state,
+                state_transitions: dfa.state_transitions.clone(),
+                accepting_states: dfa
+                    .accepting_states
+                    .iter()
+                    .map(|x| vec![x.clone()])
+                    .collect(),
+                symbol_set: dfa.symbol_set.clone(),
+            };
+            tuples.minimize();
+            tuples
+        };
+        let mut partial = minimized_tuples(dfas[0]);
+        for dfa in &dfas[1..] {
+            partial = DFA::product_many(&[&partial, &minimized_tuples(dfa)], |outputs| {
+                let mut joined = outputs[0].clone();
+                joined.extend(outputs[1].iter().cloned());
+                joined
+            });
+            partial.minimize();
+        }
+        let mut product = DFA {
+            starting_state: partial.starting_state,
+            accepting_states: partial
+                .accepting_states
+                .iter()
+                .map(|outputs| combine(&outputs.iter().collect::<Vec<_>>()))
+                .collect(),
+            state_transitions: partial.state_transitions,
+            symbol_set: partial.symbol_set,
+        };
+        //Different tuples of outputs can still combine to the same output
+        product.minimize();
+        product
+    }
+
+    //Hopcroft's algorithm, O(n·|Σ|·log n). Unreachable states are merged like any others rather than removed.
+    //States are kept in one array with every block of the partition contiguous in it. States leading into the
+    //current splitter are moved to the front of their block, so blocks can be split in place
     pub fn minimize(&mut self) {
-        let mut new_partition_membership = vec![0; self.state_transitions.len()];
-        let mut old_partition_membership = vec![0; self.state_transitions.len()];
-        let mut new_partitions: Vec<Vec<usize>> = vec![vec![], vec![]];
-        let mut old_partitions = vec![];
+        let state_count = self.state_transitions.len();
+        let symbol_count = self.symbol_set.length;
+        if state_count == 0 {
+            return;
+        }
+        //Predecessors of state t on symbol a are preds[pred_start[a * state_count + t]..pred_start[a * state_count + t + 1]]
+        let mut pred_start = vec![0; symbol_count * state_count + 1];
+        for transitions in &self.state_transitions {
+            for (symbol, target) in transitions.iter().enumerate() {
+                pred_start[symbol * state_count + target + 1] += 1;
+            }
+        }
+        for idx in 1..pred_start.len() {
+            pred_start[idx] += pred_start[idx - 1];
+        }
+        let mut next_slot = pred_start.clone();
+        let mut preds = vec![0; symbol_count * state_count];
+        for (state, transitions) in self.state_transitions.iter().enumerate() {
+            for (symbol, target) in transitions.iter().enumerate() {
+                let slot = &mut next_slot[symbol * state_count + target];
+                preds[*slot] = state;
+                *slot += 1;
+            }
+        }
 
-        let mut old_outputs = Vec::new();
-        for i in 0..self.state_transitions.len() {
-            let idx = match (&mut old_outputs.clone().into_iter())
-                .position(|x| x == self.accepting_states[i])
+        //Start with one block per output
+        let mut elements: Vec<usize> = (0..state_count).collect();
+        elements.sort_by(|a, b| self.accepting_states[*a].cmp(&self.accepting_states[*b]));
+        let mut location = vec![0; state_count];
+        let mut block_of = vec![0; state_count];
+        let mut block_start = vec![];
+        let mut block_end = vec![];
+        for (idx, state) in elements.iter().enumerate() {
+            if idx == 0 || self.accepting_states[elements[idx - 1]] != self.accepting_states[*state]
             {
-                Some(idx) => idx,
-                None => {
-                    old_outputs.push(self.accepting_states[i].clone());
-                    old_outputs.len() - 1
+                if idx > 0 {
+                    block_end.push(idx);
                 }
-            };
-            new_partition_membership[i] = idx;
-            new_partitions[idx].push(i)
+                block_start.push(idx);
+            }
+            location[*state] = idx;
+            block_of[*state] = block_start.len() - 1;
         }
+        block_end.push(state_count);
+        //How many states at the front of each block lead into the current splitter
+        let mut marked = vec![0; block_start.len()];
 
-        while new_partitions.len() > old_partitions.len() {
-            std::mem::swap(&mut old_partitions, &mut new_partitions);
-            std::mem::swap(&mut old_partition_membership, &mut new_partition_membership);
-            new_partitions.clear();
-            for partition in &old_partitions {
-                if partition.len() == 1 {
-                    new_partition_membership[partition[0]] = new_partitions.len();
-                    new_partitions.push(partition.clone());
-                    continue;
-                }
-                let mut truth_vals: Vec<Vec<usize>> = vec![];
-                let mut split_partitions: Vec<Vec<usize>> = vec![];
-                for state in partition {
-                    let mut truth_val = vec![0; self.symbol_set.length];
-                    for symbol in 0..self.symbol_set.length {
-                        truth_val[symbol] =
-                            old_partition_membership[self.state_transitions[*state][symbol]];
-                    }
-                    let mut match_found = false;
-                    for tv_idx in 0..truth_vals.len() {
-                        if truth_val == truth_vals[tv_idx] {
-                            split_partitions[tv_idx].push(*state);
-                            new_partition_membership[*state] = new_partitions.len() + tv_idx;
-                            match_found = true;
-                            break;
-                        }
+        //Splitters still to process, as (block, symbol). Every block but the largest is enough to start with
+        let mut waiting = vec![];
+        let largest = (0..block_start.len())
+            .max_by_key(|x| block_end[*x] - block_start[*x])
+            .unwrap();
+        for block in 0..block_start.len() {
+            if block != largest {
+                waiting.extend((0..symbol_count).map(|symbol| (block, symbol)));
+            }
+        }
+        let mut touched = vec![];
+        while let Some((splitter, symbol)) = waiting.pop() {
+            let members = elements[block_start[splitter]..block_end[splitter]].to_vec();
+            for target in members {
+                let target_idx = symbol * state_count + target;
+                for pred in &preds[pred_start[target_idx]..pred_start[target_idx + 1]] {
+                    let block = block_of[*pred];
+                    let front = block_start[block] + marked[block];
+                    if location[*pred] < front {
+                        continue;
                     }
-                    if !match_found {
-                        new_partition_membership[*state] = new_partitions.len() + truth_vals.len();
-                        truth_vals.push(truth_val);
-                        split_partitions.push(vec![*state]);
+                    if marked[block] == 0 {
+                        touched.push(block);
                     }
+                    let displaced = elements[front];
+                    elements.swap(front, location[*pred]);
+                    location[displaced] = location[*pred];
+                    location[*pred] = front;
+                    marked[block] += 1;
                 }
-                new_partitions.append(&mut split_partitions);
+            }
+            for block in touched.drain(..) {
+                let (start, end) = (block_start[block], block_end[block]);
+                let split = start + marked[block];
+                marked[block] = 0;
+                if split == end {
+                    continue;
+                }
+                //The smaller half becomes the new block, so each state is relabelled O(log n) times
+                let (new_range, kept_range) = if split - start <= end - split {
+                    ((start, split), (split, end))
+                } else {
+                    ((split, end), (start, split))
+                };
+                let new_block = block_start.len();
+                (block_start[block], block_end[block]) = kept_range;
+                block_start.push(new_range.0);
+                block_end.push(new_range.1);
+                marked.push(0);
+                for state in &elements[new_range.0..new_range.1] {
+                    block_of[*state] = new_block;
+                }
+                //Whether or not the old block was still waiting, only the smaller half needs adding
+                waiting.extend((0..symbol_count).map(|symbol| (new_block, symbol)));
             }
         }
-        let mut new_accepting = Vec::new();
-        for (p_index, partition) in new_partitions.iter().enumerate() {
-            new_accepting.push(self.accepting_states[partition[0]].clone());
-        }
-
-        self.starting_state = new_partition_membership[self.starting_state];
-        let mut new_transition_table = vec![];
 
-        for partition in new_partitions {
-            let mut new_transitions = vec![0; self.symbol_set.length];
-            for symbol in 0..self.symbol_set.length {
-                new_transitions[symbol] =
-                    new_partition_membership[self.state_transitions[partition[0]][symbol]];
+        //Blocks are numbered by their lowest state, so the result doesn't depend on the order of splits
+        let mut new_idxs = vec![usize::MAX; block_start.len()];
+        let mut representatives = vec![];
+        for state in 0..state_count {
+            if new_idxs[block_of[state]] == usize::MAX {
+                new_idxs[block_of[state]] = representatives.len();
+                representatives.push(state);
             }
-
-            new_transition_table.push(new_transitions);
         }
-        self.state_transitions = new_transition_table;
-        self.accepting_states = new_accepting;
+        self.starting_state = new_idxs[block_of[self.starting_state]];
+        self.state_transitions = representatives
+            .iter()
+            .map(|state| {
+                self.state_transitions[*state]
+                    .iter()
+                    .map(|target| new_idxs[block_of[*target]])
+                    .collect()
+            })
+            .collect();
+        self.accepting_states = representatives
+            .iter()
+            .map(|state| self.accepting_states[*state].clone())
+            .collect();
+    }
+
+    //Minimises in place and hands the DFA back, so it can be chained onto whatever built it
+    pub fn minimized(mut self) -> Self {
+        self.minimize();
+        self
     }
 
     pub fn ss_eq(
@@ -571,59 +555,25 @@ where
         result
     }
 
-    pub fn contains(&self, input: &Vec<SymbolIdx>) -> O {
-        let mut state = self.starting_state;
-        for i in input {
-            state = self.state_transitions[state][*i as usize];
-        }
-        self.accepting_states[state].clone()
-    }
-
-    pub fn final_state(&self, input: &Vec<SymbolIdx>) -> usize {
-        let mut state = self.starting_state;
-        for i in input {
-            state = self.state_transitions[state][*i as usize];
-        }
-        state
+    pub fn shortest_path_to_state(&self, desired: usize) -> Vec<SymbolIdx> {
+        self.path_in_tree(&self.access_tree(), desired)
     }
 
-    pub fn contains_from_start(&self, input: &Vec<SymbolIdx>, start: usize) -> O {
-        let mut state = start;
-        for i in input {
-            state = self.state_transitions[state][*i as usize];
-        }
-        self.accepting_states[state].clone()
+    //Breadth first search tree from the start, as parents for path_in_tree
+    pub(crate) fn access_tree(&self) -> Vec<Option<usize>> {
+        search::parents(
+            self.state_transitions.len(),
+            [self.starting_state],
+            |state| self.state_transitions[state].iter().copied(),
+        )
     }
 
-    pub fn shortest_path_to_state(&self, desired: usize) -> Vec<SymbolIdx> {
-        if desired == self.starting_state {
-            return vec![];
-        }
-        let mut backpath = vec![usize::MAX; self.state_transitions.len()];
-        backpath[self.starting_state] = self.starting_state;
-        let mut next_paths = vec![self.starting_state];
-        let mut old_paths = vec![];
-        let mut found_desired = false;
-        while !found_desired && !next_paths.is_empty() {
-            old_paths.clear();
-            std::mem::swap(&mut next_paths, &mut old_paths);
-            for frontier_state in &old_paths {
-                for next_spot in &self.state_transitions[*frontier_state] {
-                    if backpath[*next_spot] == usize::MAX {
-                        backpath[*next_spot] = *frontier_state;
-                        next_paths.push(*next_spot);
-                        if *next_spot == desired {
-                            found_desired = true;
-                            break;
-                        }
-                    }
-                }
-            }
-        }
+    //Shortest path from the start to a reachable state, read off its access tree
+    pub(crate) fn path_in_tree(&self, parents: &[Option<usize>], desired: usize) -> Vec<SymbolIdx> {
         let mut path: Vec<SymbolIdx> = vec![];
         let mut cur_state = desired;
         while cur_state != self.starting_state {
-            let back_state = backpath[cur_state];
+            let back_state = parents[cur_state].unwrap();
             for sym in 0..self.symbol_set.length {
                 if self.state_transitions[back_state][sym] == cur_state {
                     path.push(sym as SymbolIdx);
@@ -0,0 +1,96 @@
+//Explaining why two DFAs differ, for when two solver runs disagree.
+//The other DFA may list the same symbols in a different order, but must have the same ones; strings always use this DFA's symbol indices
+use std::fmt;
+
+use super::{SymbolIdx, SymbolSet, DFA};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DFADiff {
+    //Shortest strings accepted by the first DFA and rejected by the second, shortest first
+    pub only_first: Vec<Vec<SymbolIdx>>,
+    //Likewise the other way around
+    pub only_second: Vec<Vec<SymbolIdx>>,
+    pub symbol_set: SymbolSet,
+}
+
+impl DFADiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_first.is_empty() && self.only_second.is_empty()
+    }
+}
+
+impl fmt::Display for DFADiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Both DFAs accept the same strings");
+        }
+        for (header, strings) in [
+            ("Accepted only by the first", &self.only_first),
+            ("Accepted only by the second", &self.only_second),
+        ] {
+            if strings.is_empty() {
+                continue;
+            }
+            writeln!(f, "{}:", header)?;
+            for string in strings {
+                if string.is_empty() {
+                    writeln!(f, "    (empty string)")?;
+                } else {
+                    writeln!(f, "    {}", self.symbol_set.symbols_to_string(string))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DFA {
+    //The shortest string (then first in symbol order) that one DFA accepts and the other rejects,
+    //or None if they accept the same strings
+    pub fn distinguishing_string(&self, other: &DFA) -> Option<Vec<SymbolIdx>> {
+        (self ^ &other.aligned_to(&self.symbol_set))
+            .accepted_strings()
+            .next()
+    }
+
+    //Up to limit of the shortest strings in each direction of the symmetric difference
+    pub fn diff(&self, other: &DFA, limit: usize) -> DFADiff {
+        let other = other.aligned_to(&self.symbol_set);
+        DFADiff {
+            only_first: self
+                .dfa_product(&other, |a, b| *a && !*b)
+                .accepted_strings()
+                .take(limit)
+                .collect(),
+            only_second: self
+                .dfa_product(&other, |a, b| !*a && *b)
+                .accepted_strings()
+                .take(limit)
+                .collect(),
+            symbol_set: self.symbol_set.clone(),
+        }
+    }
+
+    //This DFA with its transitions reordered to match symbol_set, which must have the same symbols in some order
+    fn aligned_to(&self, symbol_set: &SymbolSet) -> DFA {
+        let positions: Option<Vec<usize>> = symbol_set
+            .representations
+            .iter()
+            .map(|x| self.symbol_set.representations.iter().position(|y| x == y))
+            .collect();
+        let positions = match positions {
+            Some(positions) if self.symbol_set.length == symbol_set.length => positions,
+            _ => panic!("DFAs must share a symbol set, up to the order of its symbols"),
+        };
+        DFA {
+            starting_state: self.starting_state,
+            state_transitions: self
+                .state_transitions
+                .iter()
+                .map(|x| positions.iter().map(|symbol| x[*symbol]).collect())
+                .collect(),
+            accepting_states: self.accepting_states.clone(),
+            symbol_set: symbol_set.clone(),
+        }
+    }
+}
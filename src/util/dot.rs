@@ -0,0 +1,110 @@
+//Graphviz DOT export, for looking at DFAs outside of JFLAP
+use super::DFA;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotOptions {
+    //Draw one edge per pair of states, labelled with every symbol it's taken on
+    pub merge_edges: bool,
+    //Leave out non-accepting states that can't be left, and every transition into them
+    pub hide_sink: bool,
+    //Label each state with the shortest string that reaches it
+    pub access_labels: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {
+            merge_edges: true,
+            hide_sink: false,
+            access_labels: false,
+        }
+    }
+}
+
+impl DFA {
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let state_count = self.state_transitions.len();
+        let hidden: Vec<bool> = (0..state_count)
+            .map(|state| {
+                options.hide_sink
+                    && state != self.starting_state
+                    && !self.accepting_states[state]
+                    && self.state_transitions[state].iter().all(|x| *x == state)
+            })
+            .collect();
+        let access_tree = self.access_tree();
+        let mut output = "digraph DFA {\n    rankdir=LR;\n    node [shape=circle];\n".to_owned();
+        output += "    start [shape=point];\n";
+        output += &format!("    start -> q{};\n", self.starting_state);
+        for state in (0..state_count).filter(|x| !hidden[*x]) {
+            let mut label = format!("q{}", state);
+            if options.access_labels {
+                label += "\n";
+                label += &if access_tree[state].is_none() {
+                    "unreachable".to_owned()
+                } else {
+                    match &self.path_in_tree(&access_tree, state)[..] {
+                        [] => "ε".to_owned(),
+                        path => path
+                            .iter()
+                            .map(|x| self.symbol_set.representations[*x as usize].clone())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    }
+                };
+            }
+            let shape = if self.accepting_states[state] {
+                ", shape=doublecircle"
+            } else {
+                ""
+            };
+            output += &format!("    q{} [label={}{}];\n", state, quoted(&label), shape);
+        }
+        for (state, transitions) in self.state_transitions.iter().enumerate() {
+            if hidden[state] {
+                continue;
+            }
+            //Targets in order of their first symbol, alongside every symbol leading to them
+            let mut edges: Vec<(usize, Vec<&str>)> = vec![];
+            for (symbol, target) in transitions.iter().enumerate() {
+                if hidden[*target] {
+                    continue;
+                }
+                let representation = &self.symbol_set.representations[symbol][..];
+                match edges
+                    .iter_mut()
+                    .find(|x| x.0 == *target && options.merge_edges)
+                {
+                    Some(edge) => edge.1.push(representation),
+                    None => edges.push((*target, vec![representation])),
+                }
+            }
+            for (target, symbols) in edges {
+                output += &format!(
+                    "    q{} -> q{} [label={}];\n",
+                    state,
+                    target,
+                    quoted(&symbols.join(", "))
+                );
+            }
+        }
+        output += "}\n";
+        output
+    }
+}
+
+fn quoted(label: &str) -> String {
+    let mut result = "\"".to_owned();
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
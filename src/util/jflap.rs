@@ -0,0 +1,395 @@
+//Reading and writing JFLAP (.jff) files
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+
+use xml::{
+    reader::EventReader,
+    writer::{EmitterConfig, XmlEvent},
+};
+
+use super::{SymbolIdx, SymbolSet, DFA, NFA};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+
+#[cfg(target_arch = "wasm32")]
+use rfd::FileHandle;
+#[cfg(target_arch = "wasm32")]
+type File = FileHandle;
+
+//Distance between neighbouring states in exported files, and between them and the edge of the canvas
+const LAYOUT_SPACING: f64 = 120.0;
+const LAYOUT_MARGIN: f64 = 60.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JflapError {
+    //The file isn't well-formed XML. Holds the parser's message
+    Xml(String),
+    //The file holds something other than a finite automaton (e.g. "pda" or "turing")
+    UnsupportedType(String),
+    //A <state> or <block> has no id attribute. Holds its name attribute, if it has one
+    MissingId(Option<String>),
+    //Two states share an id
+    DuplicateState(String),
+    //A transition refers to a state id that's never declared
+    UnknownState(String),
+    //A transition is missing its <from> or <to>
+    MalformedTransition,
+    //No state is marked <initial/>
+    NoInitialState,
+    //The file couldn't be read
+    Io(String),
+}
+
+impl fmt::Display for JflapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JflapError::Xml(message) => write!(f, "malformed XML: {}", message),
+            JflapError::UnsupportedType(kind) => write!(
+                f,
+                "file holds a \"{}\", only finite automata (\"fa\") are supported",
+                kind
+            ),
+            JflapError::MissingId(Some(name)) => write!(f, "state \"{}\" has no id", name),
+            JflapError::MissingId(None) => write!(f, "a state has no id"),
+            JflapError::DuplicateState(id) => {
+                write!(f, "more than one state has the id \"{}\"", id)
+            }
+            JflapError::UnknownState(id) => {
+                write!(
+                    f,
+                    "a transition uses the state \"{}\", which is never declared",
+                    id
+                )
+            }
+            JflapError::MalformedTransition => {
+                write!(f, "a transition is missing its <from> or <to>")
+            }
+            JflapError::NoInitialState => write!(f, "no state is marked as initial"),
+            JflapError::Io(message) => write!(f, "could not read file: {}", message),
+        }
+    }
+}
+
+struct JflapState {
+    id: String,
+    initial: bool,
+    accepting: bool,
+}
+
+#[derive(Default)]
+struct JflapTransition {
+    from: Option<String>,
+    to: Option<String>,
+    read: String,
+}
+
+//Whether an element with this path above it is part of the top-level automaton. JFLAP 7 wraps the automaton in
+//<automaton>, while JFLAP 6 puts it straight in <structure>. Anything deeper (like the machines inside
+//building blocks) is left alone
+fn in_automaton(parents: &[String]) -> bool {
+    match parents {
+        [structure] => structure == "structure",
+        [structure, automaton] => structure == "structure" && automaton == "automaton",
+        _ => false,
+    }
+}
+
+impl DFA {
+    //States and building blocks can come in any order relative to transitions.
+    //Nondeterministic automata (including ones with empty "lambda" transitions) are determinised
+    pub fn load_jflap_from_string(input_xml: &str) -> Result<Self, JflapError> {
+        let mut states: Vec<JflapState> = vec![];
+        let mut transitions: Vec<JflapTransition> = vec![];
+        let mut automaton_type = None;
+        //Names of the elements currently open, outermost first
+        let mut path: Vec<String> = vec![];
+        for event in EventReader::from_str(input_xml) {
+            match event.map_err(|e| JflapError::Xml(e.to_string()))? {
+                xml::reader::XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let name = name.local_name;
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|x| x.name.local_name == key)
+                            .map(|x| x.value.clone())
+                    };
+                    match &name[..] {
+                        "state" | "block" if in_automaton(&path) => {
+                            let id =
+                                attribute("id").ok_or(JflapError::MissingId(attribute("name")))?;
+                            if states.iter().any(|x| x.id == id) {
+                                return Err(JflapError::DuplicateState(id));
+                            }
+                            states.push(JflapState {
+                                id,
+                                initial: false,
+                                accepting: false,
+                            });
+                        }
+                        "initial" | "final"
+                            if path.len() > 1
+                                && in_automaton(&path[..path.len() - 1])
+                                && matches!(&path.last().unwrap()[..], "state" | "block") =>
+                        {
+                            let state = states.last_mut().unwrap();
+                            if name == "initial" {
+                                state.initial = true;
+                            } else {
+                                state.accepting = true;
+                            }
+                        }
+                        "transition" if in_automaton(&path) => {
+                            transitions.push(JflapTransition::default())
+                        }
+                        _ => {}
+                    }
+                    path.push(name);
+                }
+                xml::reader::XmlEvent::EndElement { .. } => {
+                    path.pop();
+                }
+                xml::reader::XmlEvent::Characters(chars) => {
+                    let Some((element, parents)) = path.split_last() else {
+                        continue;
+                    };
+                    match &element[..] {
+                        "type" if parents == ["structure"] => automaton_type = Some(chars),
+                        "from" | "to" | "read"
+                            if parents.last().is_some_and(|x| x == "transition")
+                                && in_automaton(&parents[..parents.len() - 1]) =>
+                        {
+                            let transition = transitions.last_mut().unwrap();
+                            match &element[..] {
+                                "from" => transition.from = Some(chars.trim().to_owned()),
+                                "to" => transition.to = Some(chars.trim().to_owned()),
+                                _ => transition.read = chars,
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(kind) = automaton_type {
+            if kind.trim() != "fa" {
+                return Err(JflapError::UnsupportedType(kind.trim().to_owned()));
+            }
+        }
+        let state_idxs: HashMap<&str, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(idx, state)| (&state.id[..], idx))
+            .collect();
+        let state_idx = |id: &Option<String>| {
+            let id = id.as_ref().ok_or(JflapError::MalformedTransition)?;
+            state_idxs
+                .get(&id[..])
+                .copied()
+                .ok_or_else(|| JflapError::UnknownState(id.clone()))
+        };
+        //Lambda transitions have an empty <read/>
+        let mut reps_vec: Vec<String> = transitions
+            .iter()
+            .filter(|x| !x.read.is_empty())
+            .map(|x| x.read.clone())
+            .collect();
+        reps_vec.sort();
+        reps_vec.dedup();
+
+        let mut nfa = NFA::new(SymbolSet {
+            length: reps_vec.len(),
+            representations: reps_vec.clone(),
+        });
+        for state in &states {
+            nfa.add_state(state.accepting);
+        }
+        nfa.starting_states = (0..states.len()).filter(|x| states[*x].initial).collect();
+        if nfa.starting_states.is_empty() {
+            return Err(JflapError::NoInitialState);
+        }
+        for transition in &transitions {
+            let (from, to) = (state_idx(&transition.from)?, state_idx(&transition.to)?);
+            match reps_vec.iter().position(|x| x == &transition.read) {
+                Some(symbol) => nfa.add_transition(from, symbol as SymbolIdx, to),
+                None => nfa.add_epsilon_transition(from, to),
+            }
+        }
+        if !nfa.is_deterministic() {
+            return Ok(nfa.to_dfa());
+        }
+
+        let mut trans_table = vec![vec![usize::MAX; reps_vec.len()]; states.len()];
+        for (state, transitions) in nfa.state_transitions.iter().enumerate() {
+            for (symbol, targets) in transitions.iter().enumerate() {
+                if let Some(target) = targets.first() {
+                    trans_table[state][symbol] = *target;
+                }
+            }
+        }
+        let mut dfa = DFA {
+            starting_state: nfa.starting_states[0],
+            state_transitions: trans_table,
+            accepting_states: nfa.accepting_states,
+            symbol_set: nfa.symbol_set,
+        };
+        dfa.complete_with_sink();
+        Ok(dfa)
+    }
+
+    pub fn save_jflap_to_bytes(&self) -> Vec<u8> {
+        let mut output_str = vec![];
+        let mut w = EmitterConfig::new()
+            .perform_indent(true)
+            .create_writer(&mut output_str);
+        w.write(XmlEvent::start_element("structure")).unwrap();
+        w.write(XmlEvent::start_element("type")).unwrap();
+        w.write(XmlEvent::characters("fa")).unwrap();
+        w.write(XmlEvent::end_element()).unwrap();
+        w.write(XmlEvent::start_element("automaton")).unwrap();
+
+        for (idx, (x, y)) in self.jflap_layout().into_iter().enumerate() {
+            w.write(
+                XmlEvent::start_element("state")
+                    .attr("id", &idx.to_string())
+                    .attr("name", &("q".to_owned() + &idx.to_string())),
+            )
+            .unwrap();
+            for (coordinate, value) in [("x", x), ("y", y)] {
+                w.write(XmlEvent::start_element(coordinate)).unwrap();
+                w.write(XmlEvent::characters(&format!("{:.1}", value)))
+                    .unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+            }
+            if idx == self.starting_state {
+                w.write(XmlEvent::start_element("initial")).unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+            }
+            if self.accepting_states[idx] {
+                w.write(XmlEvent::start_element("final")).unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+            }
+            w.write(XmlEvent::end_element()).unwrap();
+        }
+        let symbols = &self.symbol_set.representations;
+        for (idx, state) in self.state_transitions.iter().enumerate() {
+            for (idx2, target) in state.iter().enumerate() {
+                w.write(XmlEvent::start_element("transition")).unwrap();
+                w.write(XmlEvent::start_element("from")).unwrap();
+                w.write(XmlEvent::characters(&idx.to_string())).unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+                w.write(XmlEvent::start_element("to")).unwrap();
+                w.write(XmlEvent::characters(&target.to_string())).unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+                w.write(XmlEvent::start_element("read")).unwrap();
+                w.write(XmlEvent::characters(&format!("{}", symbols[idx2])))
+                    .unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+                w.write(XmlEvent::end_element()).unwrap();
+            }
+        }
+        w.write(XmlEvent::end_element()).unwrap();
+        w.write(XmlEvent::end_element()).unwrap();
+        output_str
+    }
+
+    pub fn jflap_save(&self, file: &mut File) {
+        //let mut file = fs::File::create(filename.clone().to_owned() + ".jff").unwrap();
+        let _ = file.write(&self.save_jflap_to_bytes());
+    }
+    //Where to draw each state, indexed by state. States go in columns by their distance from the start,
+    //each column ordered to keep edges from the previous one short. Non-accepting states that can't be left
+    //go below the last column, and unreachable states in a row along the bottom
+    pub fn jflap_layout(&self) -> Vec<(f64, f64)> {
+        let state_count = self.state_transitions.len();
+        let is_sink = |state: usize| {
+            state != self.starting_state
+                && !self.accepting_states[state]
+                && self.state_transitions[state].iter().all(|x| *x == state)
+        };
+        //Breadth-first, so each column starts out in the order its states were found
+        let mut depth = vec![usize::MAX; state_count];
+        depth[self.starting_state] = 0;
+        let mut columns = vec![vec![self.starting_state]];
+        let mut sinks = vec![];
+        let mut current = 0;
+        while current < columns.len() {
+            let mut next = vec![];
+            for state in &columns[current] {
+                for target in &self.state_transitions[*state] {
+                    if depth[*target] != usize::MAX {
+                        continue;
+                    }
+                    depth[*target] = current + 1;
+                    if is_sink(*target) {
+                        sinks.push(*target);
+                    } else {
+                        next.push(*target);
+                    }
+                }
+            }
+            if !next.is_empty() {
+                columns.push(next);
+            }
+            current += 1;
+        }
+        let mut row = vec![0.0; state_count];
+        for column in 1..columns.len() {
+            for (idx, state) in columns[column - 1].iter().enumerate() {
+                row[*state] = idx as f64;
+            }
+            //Average row of each state's predecessors in the column before it
+            let mut keyed: Vec<(f64, usize)> = columns[column]
+                .iter()
+                .map(|state| {
+                    let rows: Vec<f64> = columns[column - 1]
+                        .iter()
+                        .filter(|x| self.state_transitions[**x].contains(state))
+                        .map(|x| row[*x])
+                        .collect();
+                    (rows.iter().sum::<f64>() / rows.len() as f64, *state)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            columns[column] = keyed.into_iter().map(|x| x.1).collect();
+        }
+        let tallest = columns.iter().map(|x| x.len()).max().unwrap();
+        let mut layout = vec![(0.0, 0.0); state_count];
+        for (column, states) in columns.iter().enumerate() {
+            //Shorter columns are centred against the tallest one
+            let offset = (tallest - states.len()) as f64 / 2.0;
+            for (idx, state) in states.iter().enumerate() {
+                layout[*state] = (
+                    LAYOUT_MARGIN + column as f64 * LAYOUT_SPACING,
+                    LAYOUT_MARGIN + (offset + idx as f64) * LAYOUT_SPACING,
+                );
+            }
+        }
+        for (idx, sink) in sinks.iter().enumerate() {
+            layout[*sink] = (
+                LAYOUT_MARGIN + (columns.len() + idx) as f64 * LAYOUT_SPACING,
+                LAYOUT_MARGIN + tallest as f64 * LAYOUT_SPACING,
+            );
+        }
+        let unreachable = (0..state_count).filter(|x| depth[*x] == usize::MAX);
+        for (idx, state) in unreachable.enumerate() {
+            layout[state] = (
+                LAYOUT_MARGIN + idx as f64 * LAYOUT_SPACING,
+                LAYOUT_MARGIN + (tallest + 1) as f64 * LAYOUT_SPACING,
+            );
+        }
+        layout
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn jflap_load(file: &mut File) -> Result<Self, JflapError> {
+        let mut contents = "".to_owned();
+        file.read_to_string(&mut contents)
+            .map_err(|e| JflapError::Io(e.to_string()))?;
+        Self::load_jflap_from_string(&contents)
+    }
+}
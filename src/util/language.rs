@@ -0,0 +1,138 @@
+//Closure operations on the languages DFAs accept, for building goals and studying results.
+//Each returns a minimised DFA over the same symbol set; the ones taking two DFAs expect them to share it
+use std::collections::VecDeque;
+
+use super::{SymbolIdx, DFA, NFA};
+
+impl DFA {
+    //Strings made of a string this accepts followed by one other accepts
+    pub fn concat(&self, other: &DFA) -> DFA {
+        assert_same_symbols(self, other);
+        let mut nfa = NFA::from(self);
+        let offset = nfa.state_transitions.len();
+        for accepting in &other.accepting_states {
+            nfa.add_state(*accepting);
+        }
+        for (state, transitions) in other.state_transitions.iter().enumerate() {
+            for (symbol, target) in transitions.iter().enumerate() {
+                nfa.add_transition(offset + state, symbol as SymbolIdx, offset + target);
+            }
+        }
+        for state in 0..offset {
+            if nfa.accepting_states[state] {
+                nfa.accepting_states[state] = false;
+                nfa.add_epsilon_transition(state, offset + other.starting_state);
+            }
+        }
+        nfa.to_dfa().minimized()
+    }
+
+    //Zero or more strings this accepts, one after another
+    pub fn star(&self) -> DFA {
+        let mut nfa = NFA::from(self);
+        //A fresh start, so accepting the empty string doesn't make the old start accepting
+        let start = nfa.add_state(true);
+        nfa.add_epsilon_transition(start, self.starting_state);
+        for state in 0..self.state_transitions.len() {
+            if self.accepting_states[state] {
+                nfa.add_epsilon_transition(state, self.starting_state);
+            }
+        }
+        nfa.starting_states = vec![start];
+        nfa.to_dfa().minimized()
+    }
+
+    //Accepts exactly the reverses of the strings this accepts.
+    //Built by reversing the transitions as an NFA, then subset construction and minimisation
+    pub fn reverse(&self) -> DFA {
+        NFA::from(self).reverse().to_dfa().minimized()
+    }
+
+    //Strings w such that u w is accepted by this for some u accepted by prefixes
+    pub fn left_quotient(&self, prefixes: &DFA) -> DFA {
+        assert_same_symbols(self, prefixes);
+        //Every pair of states some string leads to, and with it where this can be after reading a prefix
+        let prefix_count = prefixes.state_transitions.len();
+        let mut seen = vec![false; self.state_transitions.len() * prefix_count];
+        let mut queue = VecDeque::from([(self.starting_state, prefixes.starting_state)]);
+        seen[self.starting_state * prefix_count + prefixes.starting_state] = true;
+        let mut nfa = NFA::from(self);
+        nfa.starting_states.clear();
+        while let Some((state, prefix_state)) = queue.pop_front() {
+            if prefixes.accepting_states[prefix_state] && !nfa.starting_states.contains(&state) {
+                nfa.starting_states.push(state);
+            }
+            for symbol in 0..self.symbol_set.length {
+                let next = (
+                    self.state_transitions[state][symbol],
+                    prefixes.state_transitions[prefix_state][symbol],
+                );
+                if !seen[next.0 * prefix_count + next.1] {
+                    seen[next.0 * prefix_count + next.1] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        nfa.to_dfa().minimized()
+    }
+
+    //Strings w such that w v is accepted by this for some v accepted by suffixes
+    pub fn right_quotient(&self, suffixes: &DFA) -> DFA {
+        assert_same_symbols(self, suffixes);
+        //Pairs of states from which some string takes both to accepting states, found backwards from those
+        let suffix_count = suffixes.state_transitions.len();
+        let pair = |state: usize, suffix_state: usize| state * suffix_count + suffix_state;
+        let mut predecessors = vec![vec![]; self.state_transitions.len() * suffix_count];
+        let mut queue = VecDeque::new();
+        let mut good = vec![false; predecessors.len()];
+        for state in 0..self.state_transitions.len() {
+            for suffix_state in 0..suffix_count {
+                for symbol in 0..self.symbol_set.length {
+                    predecessors[pair(
+                        self.state_transitions[state][symbol],
+                        suffixes.state_transitions[suffix_state][symbol],
+                    )]
+                    .push(pair(state, suffix_state));
+                }
+                if self.accepting_states[state] && suffixes.accepting_states[suffix_state] {
+                    good[pair(state, suffix_state)] = true;
+                    queue.push_back(pair(state, suffix_state));
+                }
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            for previous in &predecessors[current] {
+                if !good[*previous] {
+                    good[*previous] = true;
+                    queue.push_back(*previous);
+                }
+            }
+        }
+        let mut result = self.clone();
+        result.accepting_states = (0..self.state_transitions.len())
+            .map(|state| good[pair(state, suffixes.starting_state)])
+            .collect();
+        result.minimized()
+    }
+
+    //Every prefix of a string this accepts
+    pub fn prefix_closure(&self) -> DFA {
+        //Accepting exactly the states that can still reach an accepting state
+        let mut result = self.clone();
+        result.accepting_states = NFA::from(self).reverse().reachable();
+        result.minimized()
+    }
+
+    //Every suffix of a string this accepts
+    pub fn suffix_closure(&self) -> DFA {
+        let mut nfa = NFA::from(self);
+        let reachable = nfa.reachable();
+        nfa.starting_states = (0..reachable.len()).filter(|x| reachable[*x]).collect();
+        nfa.to_dfa().minimized()
+    }
+}
+
+//The same symbols in the same order, as transitions are matched up by symbol index
+fn assert_same_symbols(a: &DFA, b: &DFA) {
+    assert!(a.symbol_set == b.symbol_set, "DFAs must share a symbol set");
+}
@@ -1,8 +1,33 @@
+mod binary;
+mod counting;
 mod dfa;
+mod diff;
+mod dot;
+mod jflap;
+mod language;
+mod moore;
+mod nfa;
+mod regex;
 mod ruleset;
+mod search;
+mod strings;
+mod symmetry;
 mod symset;
+mod validate;
 
+pub use crate::util::binary::{BinaryDFAError, DFAView};
 pub use crate::util::dfa::DFA;
-pub use crate::util::ruleset::Ruleset;
+pub use crate::util::diff::DFADiff;
+pub use crate::util::dot::DotOptions;
+pub use crate::util::jflap::JflapError;
+pub use crate::util::nfa::NFA;
+pub use crate::util::regex::{RegexParseError, RegexParseErrorKind};
+pub use crate::util::ruleset::{
+    completion, confluence, termination, Anchor, RuleParseError, RuleParseErrorKind, Ruleset,
+};
+pub use crate::util::strings::ShortlexStrings;
+pub use crate::util::symmetry::Symmetry;
 pub use crate::util::symset::SymbolIdx;
 pub use crate::util::symset::SymbolSet;
+pub use crate::util::symset::MAX_SYMBOLS;
+pub use crate::util::validate::DfaError;
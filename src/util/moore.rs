@@ -0,0 +1,43 @@
+//Moore machines: DFAs whose outputs come from a finite ordered set rather than just accepting or rejecting.
+//Each one can be split into boolean DFAs, one per output value, and put back together from them
+use super::DFA;
+
+impl<I, O> DFA<I, O>
+where
+    I: Clone,
+    O: Clone + Ord,
+{
+    //Every output some state has, smallest first
+    pub fn outputs(&self) -> Vec<O> {
+        let mut outputs = self.accepting_states.clone();
+        outputs.sort();
+        outputs.dedup();
+        outputs
+    }
+
+    //Accepts the strings whose output is at least value
+    pub fn at_least(&self, value: &O) -> DFA<I, bool> {
+        DFA {
+            starting_state: self.starting_state,
+            state_transitions: self.state_transitions.clone(),
+            accepting_states: self.accepting_states.iter().map(|x| x >= value).collect(),
+            symbol_set: self.symbol_set.clone(),
+        }
+    }
+
+    //Gives each string the greatest value whose DFA accepts it, or lowest if none do.
+    //The inverse of at_least, when given it for every output above the lowest
+    pub fn from_levels(lowest: O, levels: &[(O, DFA<I, bool>)]) -> DFA<I, O> {
+        let dfas: Vec<&DFA<I, bool>> = levels.iter().map(|x| &x.1).collect();
+        DFA::product_many_minimized(&dfas, |accepted| {
+            levels
+                .iter()
+                .zip(accepted)
+                .filter(|x| **x.1)
+                .map(|x| &x.0 .0)
+                .max()
+                .unwrap_or(&lowest)
+                .clone()
+        })
+    }
+}
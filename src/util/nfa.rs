@@ -0,0 +1,173 @@
+//Nondeterministic finite automata, with epsilon transitions.
+//Mostly a stepping stone to DFAs -- goals that are easier to write nondeterministically, JFLAP files, reversal
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::{search, SymbolIdx, SymbolSet, DFA};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NFA<Input = String> {
+    //A string is accepted if it can get from any of these to an accepting state
+    pub starting_states: Vec<usize>,
+    //Every state each state can go to on each symbol, indexed by state and then symbol
+    pub state_transitions: Vec<Vec<Vec<usize>>>,
+    //States each state can go to without reading anything
+    pub epsilon_transitions: Vec<Vec<usize>>,
+    pub accepting_states: Vec<bool>,
+    pub symbol_set: SymbolSet<Input>,
+}
+
+impl<Input> NFA<Input>
+where
+    Input: Clone,
+{
+    //An NFA with no states, which accepts nothing
+    pub fn new(symbol_set: SymbolSet<Input>) -> Self {
+        NFA {
+            starting_states: vec![],
+            state_transitions: vec![],
+            epsilon_transitions: vec![],
+            accepting_states: vec![],
+            symbol_set,
+        }
+    }
+
+    pub fn add_state(&mut self, accepting: bool) -> usize {
+        self.state_transitions
+            .push(vec![vec![]; self.symbol_set.length]);
+        self.epsilon_transitions.push(vec![]);
+        self.accepting_states.push(accepting);
+        self.state_transitions.len() - 1
+    }
+
+    pub fn add_transition(&mut self, from: usize, symbol: SymbolIdx, to: usize) {
+        let targets = &mut self.state_transitions[from][symbol as usize];
+        if !targets.contains(&to) {
+            targets.push(to);
+        }
+    }
+
+    pub fn add_epsilon_transition(&mut self, from: usize, to: usize) {
+        if !self.epsilon_transitions[from].contains(&to) {
+            self.epsilon_transitions[from].push(to);
+        }
+    }
+
+    //Whether this is a DFA in all but type: one start state, no epsilon transitions,
+    //and at most one transition per state and symbol. Missing transitions are allowed
+    pub fn is_deterministic(&self) -> bool {
+        self.starting_states.len() == 1
+            && self.epsilon_transitions.iter().all(|x| x.is_empty())
+            && self
+                .state_transitions
+                .iter()
+                .all(|x| x.iter().all(|targets| targets.len() <= 1))
+    }
+
+    //Every state reachable from the given ones through epsilon transitions alone, them included
+    pub fn epsilon_closure<T>(&self, states: T) -> BTreeSet<usize>
+    where
+        T: IntoIterator<Item = usize>,
+    {
+        let mut closure = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                stack.extend(&self.epsilon_transitions[state]);
+            }
+        }
+        closure
+    }
+
+    //Whether each state can be reached from a starting state
+    pub fn reachable(&self) -> Vec<bool> {
+        search::reachable(
+            self.state_transitions.len(),
+            self.starting_states.iter().copied(),
+            |state| {
+                self.state_transitions[state]
+                    .iter()
+                    .flatten()
+                    .chain(&self.epsilon_transitions[state])
+                    .copied()
+            },
+        )
+    }
+
+    //Subset construction. Only sets reachable from the start are built, and the empty set
+    //(if it's reachable) becomes the DFA's error state
+    pub fn to_dfa(&self) -> DFA<Input, bool> {
+        let start = self.epsilon_closure(self.starting_states.iter().copied());
+        let mut sets = vec![start.clone()];
+        let mut set_idxs = HashMap::from([(start, 0)]);
+        let mut state_transitions = vec![];
+        while state_transitions.len() < sets.len() {
+            let current = sets[state_transitions.len()].clone();
+            let transitions = (0..self.symbol_set.length)
+                .map(|symbol| {
+                    let next = self.epsilon_closure(
+                        current
+                            .iter()
+                            .flat_map(|x| self.state_transitions[*x][symbol].iter().copied()),
+                    );
+                    *set_idxs.entry(next.clone()).or_insert_with(|| {
+                        sets.push(next);
+                        sets.len() - 1
+                    })
+                })
+                .collect();
+            state_transitions.push(transitions);
+        }
+        DFA {
+            starting_state: 0,
+            accepting_states: sets
+                .iter()
+                .map(|x| x.iter().any(|state| self.accepting_states[*state]))
+                .collect(),
+            state_transitions,
+            symbol_set: self.symbol_set.clone(),
+        }
+    }
+
+    //Accepts exactly the reverses of the strings this accepts
+    pub fn reverse(&self) -> Self {
+        let mut reversed = NFA::new(self.symbol_set.clone());
+        for state in 0..self.state_transitions.len() {
+            reversed.add_state(self.starting_states.contains(&state));
+        }
+        reversed.starting_states = (0..self.accepting_states.len())
+            .filter(|x| self.accepting_states[*x])
+            .collect();
+        for (state, transitions) in self.state_transitions.iter().enumerate() {
+            for (symbol, targets) in transitions.iter().enumerate() {
+                for target in targets {
+                    reversed.add_transition(*target, symbol as SymbolIdx, state);
+                }
+            }
+            for target in &self.epsilon_transitions[state] {
+                reversed.add_epsilon_transition(*target, state);
+            }
+        }
+        reversed
+    }
+}
+
+impl<Input> From<&DFA<Input, bool>> for NFA<Input>
+where
+    Input: Clone,
+{
+    fn from(dfa: &DFA<Input, bool>) -> Self {
+        NFA {
+            starting_states: vec![dfa.starting_state],
+            state_transitions: dfa
+                .state_transitions
+                .iter()
+                .map(|x| x.iter().map(|target| vec![*target]).collect())
+                .collect(),
+            epsilon_transitions: vec![vec![]; dfa.state_transitions.len()],
+            accepting_states: dfa.accepting_states.clone(),
+            symbol_set: dfa.symbol_set.clone(),
+        }
+    }
+}
@@ -0,0 +1,656 @@
+//Regular expressions over a symbol set, for writing goals by hand and for reading results back.
+//Symbols are separated by whitespace (or operators), so multi-character symbols work as-is: "0* 1 0*", "00 (01 | 10)*".
+//From loosest to tightest binding:
+//  a | b    union
+//  a & b    intersection
+//  a b      concatenation
+//  !a       complement (with respect to every string over the symbol set)
+//  a* a+ a? repetition
+//  . [0 1] [^0 1] ()   any symbol, one of these symbols, any symbol but these, the empty string
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use super::{search, SymbolSet, DFA};
+
+//A problem found while parsing a regex. Columns are 1-indexed and count characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexParseError {
+    pub column: usize,
+    pub kind: RegexParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexParseErrorKind {
+    //The symbol isn't in the symbol set
+    UnknownSymbol(String),
+    //An operator or bracket that can't appear here, e.g. "| 0" or "0 )"
+    UnexpectedToken(String),
+    //The regex ended where an operand was expected, e.g. "0 |"
+    UnexpectedEnd,
+    //A "(" with no matching ")"
+    UnclosedParen,
+    //A "[" with no matching "]"
+    UnclosedClass,
+}
+
+impl fmt::Display for RegexParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            RegexParseErrorKind::UnknownSymbol(symbol) => {
+                write!(f, "\"{}\" is not in the symbol set", symbol)
+            }
+            RegexParseErrorKind::UnexpectedToken(token) => write!(f, "unexpected \"{}\"", token),
+            RegexParseErrorKind::UnexpectedEnd => {
+                write!(f, "regex ends where an operand was expected")
+            }
+            RegexParseErrorKind::UnclosedParen => write!(f, "\"(\" is never closed"),
+            RegexParseErrorKind::UnclosedClass => write!(f, "\"[\" is never closed"),
+        }
+    }
+}
+
+const OPERATORS: &str = "()[]^*+?|&!.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Symbol(String),
+    Operator(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+            Token::Operator(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+//Each token alongside the column it starts at
+fn tokenize(input: &str) -> Vec<(usize, Token)> {
+    let mut tokens = vec![];
+    let mut symbol: Option<(usize, String)> = None;
+    for (idx, c) in input.chars().enumerate() {
+        if c.is_whitespace() || OPERATORS.contains(c) {
+            if let Some((column, symbol)) = symbol.take() {
+                tokens.push((column, Token::Symbol(symbol)));
+            }
+            if !c.is_whitespace() {
+                tokens.push((idx + 1, Token::Operator(c)));
+            }
+        } else {
+            symbol.get_or_insert((idx + 1, String::new())).1.push(c);
+        }
+    }
+    if let Some((column, symbol)) = symbol {
+        tokens.push((column, Token::Symbol(symbol)));
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    //Column just past the end of the input, for errors about running out of tokens
+    end_column: usize,
+    symbol_set: &'a SymbolSet,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|x| &x.1)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end_column, |x| x.0)
+    }
+
+    fn error(&self, kind: RegexParseErrorKind) -> RegexParseError {
+        RegexParseError {
+            column: self.column(),
+            kind,
+        }
+    }
+
+    fn unexpected(&self) -> RegexParseError {
+        match self.peek() {
+            Some(token) => self.error(RegexParseErrorKind::UnexpectedToken(token.to_string())),
+            None => self.error(RegexParseErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Operator(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn union(&mut self) -> Result<DFA, RegexParseError> {
+        let mut result = self.intersection()?;
+        while self.eat('|') {
+            result = result
+                .dfa_product(&self.intersection()?, |a, b| *a || *b)
+                .minimized();
+        }
+        Ok(result)
+    }
+
+    fn intersection(&mut self) -> Result<DFA, RegexParseError> {
+        let mut result = self.concatenation()?;
+        while self.eat('&') {
+            result = result
+                .dfa_product(&self.concatenation()?, |a, b| *a && *b)
+                .minimized();
+        }
+        Ok(result)
+    }
+
+    fn concatenation(&mut self) -> Result<DFA, RegexParseError> {
+        let mut result = self.unary()?;
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Operator('|' | '&' | ')')) {
+                break;
+            }
+            result = result.concat(&self.unary()?);
+        }
+        Ok(result)
+    }
+
+    fn unary(&mut self) -> Result<DFA, RegexParseError> {
+        if self.eat('!') {
+            let mut result = self.unary()?;
+            result.accepting_states.iter_mut().for_each(|x| *x = !*x);
+            return Ok(result);
+        }
+        let mut result = self.atom()?;
+        loop {
+            if self.eat('*') {
+                result = result.star();
+            } else if self.eat('+') {
+                result = result.concat(&result.star());
+            } else if self.eat('?') {
+                result = result
+                    .dfa_product(&symbols(self.symbol_set, &[], false, true), |a, b| *a || *b)
+                    .minimized();
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<DFA, RegexParseError> {
+        let column = self.column();
+        match self.peek().cloned() {
+            Some(Token::Symbol(symbol)) => {
+                let idx = self.symbol(&symbol)?;
+                self.pos += 1;
+                Ok(symbols(self.symbol_set, &[idx], false, false))
+            }
+            Some(Token::Operator('.')) => {
+                self.pos += 1;
+                Ok(symbols(self.symbol_set, &[], true, false))
+            }
+            Some(Token::Operator('(')) => {
+                self.pos += 1;
+                if self.eat(')') {
+                    return Ok(symbols(self.symbol_set, &[], false, true));
+                }
+                let result = self.union()?;
+                if !self.eat(')') {
+                    return Err(match self.peek() {
+                        Some(_) => self.unexpected(),
+                        None => RegexParseError {
+                            column,
+                            kind: RegexParseErrorKind::UnclosedParen,
+                        },
+                    });
+                }
+                Ok(result)
+            }
+            Some(Token::Operator('[')) => {
+                self.pos += 1;
+                let negated = self.eat('^');
+                let mut members = vec![];
+                loop {
+                    match self.peek().cloned() {
+                        Some(Token::Symbol(symbol)) => {
+                            members.push(self.symbol(&symbol)?);
+                            self.pos += 1;
+                        }
+                        Some(Token::Operator(']')) => {
+                            self.pos += 1;
+                            return Ok(symbols(self.symbol_set, &members, negated, false));
+                        }
+                        Some(_) => return Err(self.unexpected()),
+                        None => {
+                            return Err(RegexParseError {
+                                column,
+                                kind: RegexParseErrorKind::UnclosedClass,
+                            })
+                        }
+                    }
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn symbol(&self, symbol: &str) -> Result<usize, RegexParseError> {
+        self.symbol_set
+            .representations
+            .iter()
+            .position(|x| x == symbol)
+            .ok_or_else(|| self.error(RegexParseErrorKind::UnknownSymbol(symbol.to_owned())))
+    }
+}
+
+impl DFA {
+    //The minimal DFA over symbol_set accepting exactly the strings the regex matches
+    pub fn from_regex(regex: &str, symbol_set: &SymbolSet) -> Result<DFA, RegexParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(regex),
+            pos: 0,
+            end_column: regex.chars().count() + 1,
+            symbol_set,
+        };
+        let result = parser.union()?;
+        if parser.peek().is_some() {
+            return Err(parser.unexpected());
+        }
+        Ok(result.minimized())
+    }
+
+    //A regex for the DFA's language, in the syntax from_regex reads
+    pub fn to_regex(&self) -> String {
+        self.to_regex_within(usize::MAX).unwrap()
+    }
+
+    //Same as to_regex, but gives up (returning None) once any part of the regex would use more than max_symbols symbols.
+    //Uses state elimination on the trimmed minimal DFA, eliminating the state that adds the least to the regex first
+    pub fn to_regex_within(&self, max_symbols: usize) -> Option<String> {
+        let dfa = self.clone().minimized();
+        let useful = useful_states(&dfa);
+        let idx_of: HashMap<usize, usize> = useful
+            .iter()
+            .enumerate()
+            .map(|(idx, state)| (*state, idx))
+            .collect();
+        //Useful states, then a new start and a new final state
+        let (start, end) = (useful.len(), useful.len() + 1);
+        let mut edges = ExprGraph::new(useful.len() + 2);
+        if let Some(idx) = idx_of.get(&dfa.starting_state) {
+            edges.add(start, *idx, Expr::Epsilon);
+        }
+        for (idx, state) in useful.iter().enumerate() {
+            for (symbol, target) in dfa.state_transitions[*state].iter().enumerate() {
+                if let Some(target) = idx_of.get(target) {
+                    edges.add(idx, *target, Expr::Class(BTreeSet::from([symbol])));
+                }
+            }
+            if dfa.accepting_states[*state] {
+                edges.add(idx, end, Expr::Epsilon);
+            }
+        }
+        let mut remaining: Vec<usize> = (0..useful.len()).collect();
+        while !remaining.is_empty() {
+            let (pos, _) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, state)| edges.elimination_weight(**state))
+                .unwrap();
+            let state = remaining.remove(pos);
+            let (repeat, ins, outs) = edges.remove_state(state);
+            let repeat = Expr::star(repeat);
+            for (from, into) in &ins {
+                for (to, out) in &outs {
+                    let path = Expr::concat(vec![into.clone(), repeat.clone(), out.clone()]);
+                    if edges.add(*from, *to, path).size() > max_symbols {
+                        return None;
+                    }
+                }
+            }
+        }
+        let mut result = String::new();
+        edges.get(start, end).write(&dfa.symbol_set, 0, &mut result);
+        Some(result)
+    }
+}
+
+//Single symbols from the given ones (or, when negated, from every other one), plus the empty string if asked for
+fn symbols(symbol_set: &SymbolSet, members: &[usize], negated: bool, empty: bool) -> DFA {
+    //States are start, after one symbol, and dead
+    let mut start = vec![2; symbol_set.length];
+    for (symbol, target) in start.iter_mut().enumerate() {
+        if members.contains(&symbol) != negated {
+            *target = 1;
+        }
+    }
+    DFA {
+        starting_state: 0,
+        state_transitions: vec![
+            start,
+            vec![2; symbol_set.length],
+            vec![2; symbol_set.length],
+        ],
+        accepting_states: vec![empty, true, false],
+        symbol_set: symbol_set.clone(),
+    }
+}
+
+//States that are reachable from the start and can reach an accepting state, in increasing order
+fn useful_states(dfa: &DFA) -> Vec<usize> {
+    let mut reversed = vec![vec![]; dfa.state_transitions.len()];
+    for (state, transitions) in dfa.state_transitions.iter().enumerate() {
+        for target in transitions {
+            reversed[*target].push(state);
+        }
+    }
+    let state_count = dfa.state_transitions.len();
+    let reachable = search::reachable(state_count, [dfa.starting_state], |state| {
+        dfa.state_transitions[state].iter().copied()
+    });
+    let accepting = (0..state_count).filter(|x| dfa.accepting_states[*x]);
+    let coreachable = search::reachable(state_count, accepting, |state| {
+        reversed[state].iter().copied()
+    });
+    (0..state_count)
+        .filter(|x| reachable[*x] && coreachable[*x])
+        .collect()
+}
+
+//Edges between states during state elimination, kept sparse as most pairs of states have no edge
+struct ExprGraph {
+    outs: Vec<BTreeMap<usize, Expr>>,
+    ins: Vec<BTreeSet<usize>>,
+}
+
+impl ExprGraph {
+    fn new(states: usize) -> Self {
+        ExprGraph {
+            outs: vec![BTreeMap::new(); states],
+            ins: vec![BTreeSet::new(); states],
+        }
+    }
+
+    fn get(&self, from: usize, to: usize) -> Expr {
+        self.outs[from].get(&to).cloned().unwrap_or(Expr::Empty)
+    }
+
+    //Unions expr into the edge, returning the new edge
+    fn add(&mut self, from: usize, to: usize, expr: Expr) -> &Expr {
+        let edge = self.outs[from].entry(to).or_insert(Expr::Empty);
+        *edge = Expr::union(edge.clone(), expr);
+        self.ins[to].insert(from);
+        edge
+    }
+
+    //Disconnects the state, returning its self loop and its edges in and out (in ascending order)
+    fn remove_state(&mut self, state: usize) -> (Expr, Vec<(usize, Expr)>, Vec<(usize, Expr)>) {
+        let repeat = self.outs[state].remove(&state).unwrap_or(Expr::Empty);
+        self.ins[state].remove(&state);
+        let outs: Vec<(usize, Expr)> = std::mem::take(&mut self.outs[state]).into_iter().collect();
+        for (to, _) in &outs {
+            self.ins[*to].remove(&state);
+        }
+        let ins = std::mem::take(&mut self.ins[state])
+            .into_iter()
+            .map(|from| (from, self.outs[from].remove(&state).unwrap()))
+            .collect();
+        (repeat, ins, outs)
+    }
+
+    //How many symbols eliminating the state adds to the regex, roughly (Delgado & Morais' heuristic)
+    fn elimination_weight(&self, state: usize) -> isize {
+        let ins: Vec<isize> = self.ins[state]
+            .iter()
+            .filter(|x| **x != state)
+            .map(|x| self.outs[*x][&state].size() as isize)
+            .collect();
+        let outs: Vec<isize> = self.outs[state]
+            .iter()
+            .filter(|(x, _)| **x != state)
+            .map(|(_, expr)| expr.size() as isize)
+            .collect();
+        let (in_count, out_count) = (ins.len() as isize, outs.len() as isize);
+        ins.iter().sum::<isize>() * (out_count - 1)
+            + outs.iter().sum::<isize>() * (in_count - 1)
+            + self.get(state, state).size() as isize * (in_count * out_count - 1)
+    }
+}
+
+//Regexes built during state elimination. Only made through union, concat and star, which keep them simplified
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Expr {
+    //Matches nothing
+    Empty,
+    Epsilon,
+    //Any one of these symbols
+    Class(BTreeSet<usize>),
+    Star(Box<Expr>),
+    Concat(Vec<Expr>),
+    //Sorted, and never holds more than one class
+    Union(Vec<Expr>),
+}
+
+impl Expr {
+    //Number of symbols written out
+    fn size(&self) -> usize {
+        match self {
+            Expr::Empty | Expr::Epsilon => 0,
+            Expr::Class(_) => 1,
+            Expr::Star(inner) => inner.size(),
+            Expr::Concat(parts) | Expr::Union(parts) => parts.iter().map(|x| x.size()).sum(),
+        }
+    }
+
+    fn nullable(&self) -> bool {
+        match self {
+            Expr::Empty | Expr::Class(_) => false,
+            Expr::Epsilon | Expr::Star(_) => true,
+            Expr::Concat(parts) => parts.iter().all(|x| x.nullable()),
+            Expr::Union(parts) => parts.iter().any(|x| x.nullable()),
+        }
+    }
+
+    fn parts(&self) -> &[Expr] {
+        match self {
+            Expr::Concat(parts) => parts,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    //x when this is x x*
+    fn plus_inner(&self) -> Option<Expr> {
+        if let Expr::Concat(parts) = self {
+            if let Some((Expr::Star(inner), rest)) = parts.split_last() {
+                if rest == inner.parts() {
+                    return Some((**inner).clone());
+                }
+            }
+        }
+        None
+    }
+
+    //Precedence is 0 for anything, 1 inside a concatenation, and 2 under a postfix operator
+    fn write(&self, symbol_set: &SymbolSet, precedence: usize, out: &mut String) {
+        match self {
+            Expr::Empty => out.push_str(if precedence > 0 { "(!.*)" } else { "!.*" }),
+            Expr::Epsilon => out.push_str("()"),
+            Expr::Class(members) => {
+                let names = |symbols: &mut dyn Iterator<Item = usize>| {
+                    symbols
+                        .map(|x| symbol_set.representations[x].clone())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                if members.len() == 1 {
+                    out.push_str(&names(&mut members.iter().copied()));
+                } else if members.len() == symbol_set.length {
+                    out.push('.');
+                } else if members.len() * 2 > symbol_set.length {
+                    let mut others = (0..symbol_set.length).filter(|x| !members.contains(x));
+                    out.push_str(&format!("[^{}]", names(&mut others)));
+                } else {
+                    out.push_str(&format!("[{}]", names(&mut members.iter().copied())));
+                }
+            }
+            Expr::Star(inner) => {
+                inner.write(symbol_set, 2, out);
+                out.push('*');
+            }
+            Expr::Concat(parts) => {
+                //x x* is written x+
+                let mut pieces: Vec<(&Expr, bool)> = vec![];
+                for part in parts {
+                    if let Expr::Star(inner) = part {
+                        let repeated = inner.parts();
+                        if pieces.len() >= repeated.len()
+                            && pieces[pieces.len() - repeated.len()..]
+                                .iter()
+                                .zip(repeated)
+                                .all(|((piece, plus), x)| !plus && *piece == x)
+                        {
+                            pieces.truncate(pieces.len() - repeated.len());
+                            pieces.push((inner, true));
+                            continue;
+                        }
+                    }
+                    pieces.push((part, false));
+                }
+                if pieces.len() == 1 {
+                    pieces[0].0.write(symbol_set, 2, out);
+                    out.push('+');
+                    return;
+                }
+                if precedence > 1 {
+                    out.push('(');
+                }
+                for (idx, (piece, plus)) in pieces.iter().enumerate() {
+                    if idx > 0 {
+                        out.push(' ');
+                    }
+                    if *plus {
+                        piece.write(symbol_set, 2, out);
+                        out.push('+');
+                    } else {
+                        piece.write(symbol_set, 1, out);
+                    }
+                }
+                if precedence > 1 {
+                    out.push(')');
+                }
+            }
+            Expr::Union(options) => {
+                if options.contains(&Expr::Epsilon) {
+                    let rest = options.iter().filter(|x| **x != Expr::Epsilon).cloned();
+                    rest.fold(Expr::Empty, Expr::union)
+                        .write(symbol_set, 2, out);
+                    out.push('?');
+                    return;
+                }
+                if precedence > 0 {
+                    out.push('(');
+                }
+                for (idx, option) in options.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(" | ");
+                    }
+                    option.write(symbol_set, 0, out);
+                }
+                if precedence > 0 {
+                    out.push(')');
+                }
+            }
+        }
+    }
+}
+
+impl Expr {
+    fn union(a: Expr, b: Expr) -> Expr {
+        let mut options = vec![];
+        let mut class = BTreeSet::new();
+        for option in [a, b] {
+            let flattened = match option {
+                Expr::Union(options) => options,
+                other => vec![other],
+            };
+            for option in flattened {
+                match option {
+                    Expr::Empty => {}
+                    Expr::Class(members) => class.extend(members),
+                    other => options.push(other),
+                }
+            }
+        }
+        if !class.is_empty() {
+            options.push(Expr::Class(class));
+        }
+        options.sort();
+        options.dedup();
+        //x | x* is x*, and () is redundant next to anything else that matches it
+        let stars: Vec<Expr> = options
+            .iter()
+            .filter_map(|x| match x {
+                Expr::Star(inner) => Some((**inner).clone()),
+                _ => None,
+            })
+            .collect();
+        options.retain(|x| !stars.contains(x));
+        //x x* | () is x*
+        if options.contains(&Expr::Epsilon) {
+            for option in options.iter_mut() {
+                if let Some(inner) = option.plus_inner() {
+                    *option = Expr::Star(Box::new(inner));
+                }
+            }
+            options.sort();
+            options.dedup();
+        }
+        if options.iter().filter(|x| x.nullable()).count() > 1 {
+            options.retain(|x| *x != Expr::Epsilon);
+        }
+        match options.len() {
+            0 => Expr::Empty,
+            1 => options.pop().unwrap(),
+            _ => Expr::Union(options),
+        }
+    }
+
+    fn concat(parts: Vec<Expr>) -> Expr {
+        let mut flattened: Vec<Expr> = vec![];
+        for part in parts {
+            match part {
+                Expr::Empty => return Expr::Empty,
+                Expr::Epsilon => {}
+                Expr::Concat(inner) => flattened.extend(inner),
+                //x* x* is x*
+                other if matches!(other, Expr::Star(_)) && flattened.last() == Some(&other) => {}
+                other => flattened.push(other),
+            }
+        }
+        match flattened.len() {
+            0 => Expr::Epsilon,
+            1 => flattened.pop().unwrap(),
+            _ => Expr::Concat(flattened),
+        }
+    }
+
+    fn star(inner: Expr) -> Expr {
+        match inner {
+            Expr::Empty | Expr::Epsilon => Expr::Epsilon,
+            Expr::Star(_) => inner,
+            //(x | ())* is x*
+            Expr::Union(options) if options.contains(&Expr::Epsilon) => Expr::star(
+                options
+                    .into_iter()
+                    .filter(|x| *x != Expr::Epsilon)
+                    .fold(Expr::Empty, Expr::union),
+            ),
+            other => Expr::Star(Box::new(other)),
+        }
+    }
+}
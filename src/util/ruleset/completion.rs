@@ -0,0 +1,150 @@
+//Knuth-Bendix completion.
+//Reads every rule as an equation, and looks for a terminating, confluent ruleset with the same equations.
+//Note that equivalence here is in both directions -- the completed ruleset generates the same congruence,
+//not necessarily the same one-way reachability the original rules had.
+use std::collections::HashMap;
+
+use super::Ruleset;
+use crate::util::SymbolIdx;
+
+//A well-founded order on strings, used to decide which way around each equation becomes a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReductionOrder {
+    //Shorter strings are smaller, and strings of the same length are compared lexicographically.
+    //Holds the symbol precedence, listed from smallest to largest
+    ShortLex(Vec<SymbolIdx>),
+    //Strings with a lower total weight are smaller, ties broken by ShortLex. Holds (weights by symbol, precedence)
+    WeightLex(Vec<usize>, Vec<SymbolIdx>),
+}
+
+impl ReductionOrder {
+    pub fn greater(&self, a: &[SymbolIdx], b: &[SymbolIdx]) -> bool {
+        match self {
+            ReductionOrder::ShortLex(precedence) => shortlex_greater(precedence, a, b),
+            ReductionOrder::WeightLex(weights, precedence) => {
+                let weight =
+                    |x: &[SymbolIdx]| x.iter().map(|sym| weights[*sym as usize]).sum::<usize>();
+                let (a_weight, b_weight) = (weight(a), weight(b));
+                a_weight > b_weight || (a_weight == b_weight && shortlex_greater(precedence, a, b))
+            }
+        }
+    }
+}
+
+fn shortlex_greater(precedence: &[SymbolIdx], a: &[SymbolIdx], b: &[SymbolIdx]) -> bool {
+    if a.len() != b.len() {
+        return a.len() > b.len();
+    }
+    let rank = |sym: &SymbolIdx| precedence.iter().position(|x| x == sym);
+    a.iter().map(rank).gt(b.iter().map(rank))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompletionBudget {
+    //Give up once the ruleset being built has more rules than this
+    pub max_rules: usize,
+    //Give up after this many rounds of adding critical pairs
+    pub max_rounds: usize,
+}
+
+impl Default for CompletionBudget {
+    fn default() -> Self {
+        CompletionBudget {
+            max_rules: 200,
+            max_rounds: 20,
+        }
+    }
+}
+
+impl Ruleset {
+    //Ok holds the completed ruleset. Err holds the rules built so far if the budget ran out,
+    //an equation couldn't be oriented, or the ruleset has anchored rules (which completion doesn't support)
+    pub fn complete(
+        &self,
+        order: &ReductionOrder,
+        budget: CompletionBudget,
+    ) -> Result<Ruleset, Ruleset> {
+        if !self.anchored_rules.is_empty() {
+            return Err(self.clone());
+        }
+        let mut equations: Vec<(Vec<SymbolIdx>, Vec<SymbolIdx>)> = self
+            .rule_pairs()
+            .map(|(lhs, rhs)| (lhs.clone(), rhs.clone()))
+            .collect();
+        //Popped from the back, so this keeps the original rules first
+        equations.reverse();
+        let mut rules: Vec<(Vec<SymbolIdx>, Vec<SymbolIdx>)> = vec![];
+        for _ in 0..budget.max_rounds {
+            while let Some((a, b)) = equations.pop() {
+                let a = normal_form(&rules, a);
+                let b = normal_form(&rules, b);
+                if a == b {
+                    continue;
+                }
+                let (lhs, rhs) = if order.greater(&a, &b) {
+                    (a, b)
+                } else if order.greater(&b, &a) {
+                    (b, a)
+                } else {
+                    return Err(self.with_rules(rules));
+                };
+                //Rules the new one can rewrite the LHS of are no longer needed as-is, so they go back to being equations
+                let (reducible, kept): (Vec<_>, Vec<_>) = rules
+                    .into_iter()
+                    .partition(|(old_lhs, _)| contains(old_lhs, &lhs));
+                equations.extend(reducible);
+                rules = kept;
+                rules.push((lhs, rhs));
+                for idx in 0..rules.len() {
+                    let rhs = normal_form(&rules, rules[idx].1.clone());
+                    rules[idx].1 = rhs;
+                }
+                if rules.len() > budget.max_rules {
+                    return Err(self.with_rules(rules));
+                }
+            }
+            let current = self.with_rules(rules.clone());
+            for pair in current.critical_pairs() {
+                let left = normal_form(&rules, pair.left);
+                let right = normal_form(&rules, pair.right);
+                if left != right {
+                    equations.push((left, right));
+                }
+            }
+            if equations.is_empty() {
+                return Ok(current);
+            }
+        }
+        Err(self.with_rules(rules))
+    }
+
+    fn with_rules(&self, rules: Vec<(Vec<SymbolIdx>, Vec<SymbolIdx>)>) -> Ruleset {
+        let mut rule_hash: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>> = HashMap::new();
+        for (lhs, rhs) in rules {
+            rule_hash.entry(lhs).or_default().push(rhs);
+        }
+        Ruleset::with_anchored_rules(rule_hash, vec![], self.symbol_set.clone())
+    }
+}
+
+fn contains(string: &[SymbolIdx], factor: &[SymbolIdx]) -> bool {
+    factor.is_empty() || string.windows(factor.len()).any(|x| x == factor)
+}
+
+//Rewrites the leftmost match until nothing applies. Only terminates because every rule is oriented by the order
+fn normal_form(
+    rules: &[(Vec<SymbolIdx>, Vec<SymbolIdx>)],
+    mut string: Vec<SymbolIdx>,
+) -> Vec<SymbolIdx> {
+    'rewriting: loop {
+        for idx in 0..string.len() {
+            for (lhs, rhs) in rules {
+                if string[idx..].starts_with(lhs) {
+                    string.splice(idx..idx + lhs.len(), rhs.iter().copied());
+                    continue 'rewriting;
+                }
+            }
+        }
+        return string;
+    }
+}
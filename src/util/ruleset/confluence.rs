@@ -0,0 +1,169 @@
+//Critical pairs and local confluence.
+//Two rules overlap if their LHSs can share symbols in some string. Applying each of them to that string
+//gives a critical pair, and a ruleset is locally confluent when every critical pair can be rewritten back together.
+use std::collections::HashSet;
+
+use super::{Anchor, Ruleset};
+use crate::util::{SymbolIdx, SymbolSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CriticalPair {
+    //Smallest string both rules apply to
+    pub overlap: Vec<SymbolIdx>,
+    //Which ends of the string the overlap has to sit at, if either of the rules is anchored
+    pub anchor: Option<Anchor>,
+    //The overlap after applying the first rule
+    pub left: Vec<SymbolIdx>,
+    //The overlap after applying the second rule
+    pub right: Vec<SymbolIdx>,
+}
+
+impl CriticalPair {
+    pub fn to_string(&self, symset: &SymbolSet) -> String {
+        format!(
+            "{}{}{} can be rewritten to both {} and {}",
+            if self.anchor.is_some_and(|x| x.at_start()) {
+                "^ "
+            } else {
+                ""
+            },
+            symset.symbols_to_string(&self.overlap),
+            if self.anchor.is_some_and(|x| x.at_end()) {
+                " $"
+            } else {
+                ""
+            },
+            symset.symbols_to_string(&self.left),
+            symset.symbols_to_string(&self.right)
+        )
+    }
+
+    fn at_start(&self) -> bool {
+        self.anchor.is_some_and(|x| x.at_start())
+    }
+
+    fn at_end(&self) -> bool {
+        self.anchor.is_some_and(|x| x.at_end())
+    }
+}
+
+fn pin(at_start: bool, at_end: bool) -> Option<Anchor> {
+    match (at_start, at_end) {
+        (true, true) => Some(Anchor::Both),
+        (true, false) => Some(Anchor::Start),
+        (false, true) => Some(Anchor::End),
+        (false, false) => None,
+    }
+}
+
+impl Ruleset {
+    //Every critical pair between two rules (or a rule and itself), sorted.
+    //Pairs whose two sides are already equal are left out
+    pub fn critical_pairs(&self) -> Vec<CriticalPair> {
+        let rules: Vec<(Option<Anchor>, &Vec<SymbolIdx>, &Vec<SymbolIdx>)> = self
+            .rules
+            .iter()
+            .flat_map(|(lhs, rhs_list)| rhs_list.iter().map(move |rhs| (None, lhs, rhs)))
+            .chain(
+                self.anchored_rules
+                    .iter()
+                    .map(|(anchor, lhs, rhs)| (Some(*anchor), lhs, rhs)),
+            )
+            .collect();
+        let starts = |anchor: Option<Anchor>| anchor.is_some_and(|x| x.at_start());
+        let ends = |anchor: Option<Anchor>| anchor.is_some_and(|x| x.at_end());
+        let mut result = HashSet::new();
+        for (first_idx, (first_anchor, first_lhs, first_rhs)) in rules.iter().enumerate() {
+            for (second_idx, (second_anchor, second_lhs, second_rhs)) in rules.iter().enumerate() {
+                //The second LHS sits inside the first one
+                if second_lhs.len() <= first_lhs.len() {
+                    for idx in 0..=(first_lhs.len() - second_lhs.len()) {
+                        let end = idx + second_lhs.len();
+                        if (first_idx == second_idx && idx == 0)
+                            || first_lhs[idx..end] != second_lhs[..]
+                            || (starts(*second_anchor) && idx != 0)
+                            || (ends(*second_anchor) && end != first_lhs.len())
+                        {
+                            continue;
+                        }
+                        let mut right = first_lhs[..idx].to_vec();
+                        right.extend(second_rhs.iter());
+                        right.extend(&first_lhs[end..]);
+                        result.insert(CriticalPair {
+                            overlap: first_lhs.to_vec(),
+                            anchor: pin(
+                                starts(*first_anchor) || starts(*second_anchor),
+                                ends(*first_anchor) || ends(*second_anchor),
+                            ),
+                            left: first_rhs.to_vec(),
+                            right,
+                        });
+                    }
+                }
+                //The end of the first LHS is the start of the second one
+                if ends(*first_anchor) || starts(*second_anchor) {
+                    continue;
+                }
+                for shared in 1..first_lhs.len().min(second_lhs.len()) {
+                    if first_lhs[first_lhs.len() - shared..] != second_lhs[..shared] {
+                        continue;
+                    }
+                    let mut overlap = first_lhs.to_vec();
+                    overlap.extend(&second_lhs[shared..]);
+                    let mut left = first_rhs.to_vec();
+                    left.extend(&second_lhs[shared..]);
+                    let mut right = first_lhs[..first_lhs.len() - shared].to_vec();
+                    right.extend(second_rhs.iter());
+                    result.insert(CriticalPair {
+                        overlap,
+                        anchor: pin(starts(*first_anchor), ends(*second_anchor)),
+                        left,
+                        right,
+                    });
+                }
+            }
+        }
+        let mut result: Vec<_> = result.into_iter().filter(|x| x.left != x.right).collect();
+        result.sort();
+        result
+    }
+
+    //Critical pairs whose sides can't be rewritten into a common string within max_depth rule applications each.
+    //An empty result means the ruleset is locally confluent (as far as the depth bound can tell)
+    pub fn check_local_confluence(&self, max_depth: usize) -> Vec<CriticalPair> {
+        self.critical_pairs()
+            .into_iter()
+            .filter(|pair| {
+                let left =
+                    self.reachable_within(&pair.left, max_depth, pair.at_start(), pair.at_end());
+                let right =
+                    self.reachable_within(&pair.right, max_depth, pair.at_start(), pair.at_end());
+                left.is_disjoint(&right)
+            })
+            .collect()
+    }
+
+    //Every string reachable from start in at most max_depth rule applications, start included
+    pub(crate) fn reachable_within(
+        &self,
+        start: &[SymbolIdx],
+        max_depth: usize,
+        at_start: bool,
+        at_end: bool,
+    ) -> HashSet<Vec<SymbolIdx>> {
+        let mut reached = HashSet::from([start.to_vec()]);
+        let mut frontier = vec![start.to_vec()];
+        for _ in 0..max_depth {
+            let mut next_frontier = vec![];
+            for string in &frontier {
+                for next in self.rewrites_within(string, at_start, at_end) {
+                    if reached.insert(next.clone()) {
+                        next_frontier.push(next);
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+        reached
+    }
+}
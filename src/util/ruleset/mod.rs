@@ -0,0 +1,946 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{SymbolIdx, SymbolSet, MAX_SYMBOLS};
+
+pub mod completion;
+pub mod confluence;
+pub mod termination;
+
+//A single problem found while parsing an SRS file. Lines and columns are 1-indexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: RuleParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleParseErrorKind {
+    //The file contains no rules at all (only whitespace and/or comments)
+    EmptyFile,
+    //A "^" anywhere but the start of the LHS, or a "$" anywhere but the end of it
+    MisplacedAnchor,
+    //A line contains more than one "-", e.g. "1 - 0 - 1"
+    ExtraSeparator,
+    //The same LHS - RHS pair was already declared on an earlier line
+    DuplicateRule {
+        first_line: usize,
+    },
+    //The LHS and RHS are the same string, so the rule does nothing
+    IdenticalSides,
+    //The LHS is empty, so the rule can be applied anywhere and keeps generating forever
+    EmptyLhs,
+    //The "where" clause of a schema isn't of the form "x in {0,1}, y in class_name"
+    InvalidWhereClause,
+    //A schema variable was bound twice in the same "where" clause
+    DuplicateVariable(String),
+    //A schema variable doesn't appear in either side of its rule
+    UnusedVariable(String),
+    //A schema refers to a class that hasn't been declared (yet)
+    UndefinedClass(String),
+    //A class declaration isn't of the form "class name = {0,1}"
+    InvalidClass,
+    //An include directive isn't of the form include "file.srs"
+    InvalidInclude,
+    //The included file could not be found/read
+    IncludeNotFound(String),
+    //The included file (indirectly) includes itself
+    IncludeCycle(String),
+    //This line introduces more symbols than SymbolIdx can hold. Holds how many the file uses in total
+    TooManySymbols(usize),
+    //The included file has problems of its own
+    Include {
+        path: String,
+        errors: Vec<RuleParseError>,
+    },
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            RuleParseErrorKind::EmptyFile => write!(f, "SRS contains no rules"),
+            RuleParseErrorKind::ExtraSeparator => {
+                write!(
+                    f,
+                    "extra \"-\" separator, a rule must be of the form \"LHS - RHS\""
+                )
+            }
+            RuleParseErrorKind::DuplicateRule { first_line } => {
+                write!(f, "duplicate rule, first declared on line {}", first_line)
+            }
+            RuleParseErrorKind::IdenticalSides => write!(f, "LHS and RHS of rule are identical"),
+            RuleParseErrorKind::EmptyLhs => {
+                write!(f, "LHS of rule is empty, so it could be applied anywhere")
+            }
+            RuleParseErrorKind::MisplacedAnchor => write!(
+                f,
+                "anchors must be written on the LHS, \"^\" first and/or \"$\" last"
+            ),
+            RuleParseErrorKind::InvalidWhereClause => write!(
+                f,
+                "malformed where clause, expected e.g. \"where x in {{0,1}}, y in class_name\""
+            ),
+            RuleParseErrorKind::DuplicateVariable(var) => {
+                write!(f, "variable \"{}\" is bound more than once", var)
+            }
+            RuleParseErrorKind::UnusedVariable(var) => {
+                write!(f, "variable \"{}\" is not used in the rule", var)
+            }
+            RuleParseErrorKind::UndefinedClass(class) => {
+                write!(f, "class \"{}\" has not been declared", class)
+            }
+            RuleParseErrorKind::InvalidClass => write!(
+                f,
+                "malformed class declaration, expected e.g. \"class name = {{0,1}}\""
+            ),
+            RuleParseErrorKind::InvalidInclude => {
+                write!(f, "malformed include, expected e.g. include \"other.srs\"")
+            }
+            RuleParseErrorKind::IncludeNotFound(path) => {
+                write!(f, "could not read included file \"{}\"", path)
+            }
+            RuleParseErrorKind::IncludeCycle(path) => {
+                write!(f, "\"{}\" is included within itself", path)
+            }
+            RuleParseErrorKind::TooManySymbols(count) => write!(
+                f,
+                "SRS uses {} symbols, but at most {} are supported{}",
+                count,
+                MAX_SYMBOLS,
+                if cfg!(feature = "wide_symbols") {
+                    ""
+                } else {
+                    " (build with the wide_symbols feature for more)"
+                }
+            ),
+            RuleParseErrorKind::Include { path, errors } => {
+                write!(f, "in included file \"{}\": ", path)?;
+                for (idx, error) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+//Where in a string an anchored rule is allowed to be applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Anchor {
+    //"^ 1 1 0 - 0 0 1", the LHS must be at the very start of the string
+    Start,
+    //"1 1 0 $ - 0 0 1", the LHS must be at the very end of the string
+    End,
+    //"^ 1 1 0 $ - 0 0 1", the LHS must be the whole string
+    Both,
+}
+
+impl Anchor {
+    pub fn at_start(&self) -> bool {
+        matches!(self, Anchor::Start | Anchor::Both)
+    }
+    pub fn at_end(&self) -> bool {
+        matches!(self, Anchor::End | Anchor::Both)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    #[serde(with = "rule_map")]
+    pub rules: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>>,
+    //Rules that may only be applied at the start and/or end of a string, as (anchor, LHS, RHS).
+    //Kept apart from rules so that everything that only understands unanchored rules can keep using that map as-is
+    pub anchored_rules: Vec<(Anchor, Vec<SymbolIdx>, Vec<SymbolIdx>)>,
+    pub symbol_set: SymbolSet,
+    pub max_input: usize,
+    pub min_input: usize,
+}
+
+impl Ruleset {
+    pub fn new(rules: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>>, symbol_set: SymbolSet) -> Self {
+        Ruleset {
+            symbol_set: symbol_set,
+            max_input: rules.keys().max_by_key(|x| x.len()).unwrap().len(),
+            min_input: rules.keys().min_by_key(|x| x.len()).unwrap().len(),
+            rules: rules,
+            anchored_rules: vec![],
+        }
+    }
+
+    pub fn with_anchored_rules(
+        rules: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>>,
+        anchored_rules: Vec<(Anchor, Vec<SymbolIdx>, Vec<SymbolIdx>)>,
+        symbol_set: SymbolSet,
+    ) -> Self {
+        let lhs_lens = rules
+            .keys()
+            .map(|lhs| lhs.len())
+            .chain(anchored_rules.iter().map(|(_, lhs, _)| lhs.len()));
+        Ruleset {
+            symbol_set,
+            max_input: lhs_lens.clone().max().unwrap_or(0),
+            min_input: lhs_lens.min().unwrap_or(0),
+            rules,
+            anchored_rules,
+        }
+    }
+
+    //Every rule as an (LHS, RHS) pair, ignoring anchors
+    pub fn rule_pairs(&self) -> impl Iterator<Item = (&Vec<SymbolIdx>, &Vec<SymbolIdx>)> {
+        self.rules
+            .iter()
+            .flat_map(|(lhs, rhs_list)| rhs_list.iter().map(move |rhs| (lhs, rhs)))
+            .chain(self.anchored_rules.iter().map(|(_, lhs, rhs)| (lhs, rhs)))
+    }
+
+    //Every string reachable from this one in a single rule application
+    pub fn rewrites(&self, string: &[SymbolIdx]) -> Vec<Vec<SymbolIdx>> {
+        self.rewrites_within(string, true, true)
+    }
+
+    //Same as rewrites, for a string that may have more symbols before (at_start = false) or after (at_end = false) it,
+    //in which case rules anchored to that end can't be applied
+    pub fn rewrites_within(
+        &self,
+        string: &[SymbolIdx],
+        at_start: bool,
+        at_end: bool,
+    ) -> Vec<Vec<SymbolIdx>> {
+        let mut result = vec![];
+        let mut apply = |idx: usize, lhs_len: usize, rhs: &Vec<SymbolIdx>| {
+            let mut new_string = string[..idx].to_vec();
+            new_string.extend(rhs);
+            new_string.extend(&string[idx + lhs_len..]);
+            result.push(new_string);
+        };
+        for (lhs, rhs_list) in &self.rules {
+            for idx in 0..=string.len() {
+                if string[idx..].starts_with(lhs) {
+                    for rhs in rhs_list {
+                        apply(idx, lhs.len(), rhs);
+                    }
+                }
+            }
+        }
+        for (anchor, lhs, rhs) in &self.anchored_rules {
+            if lhs.len() > string.len()
+                || (anchor.at_start() && !at_start)
+                || (anchor.at_end() && !at_end)
+            {
+                continue;
+            }
+            let idx = if anchor.at_start() {
+                0
+            } else {
+                string.len() - lhs.len()
+            };
+            if (!anchor.at_end() || idx + lhs.len() == string.len())
+                && string[idx..].starts_with(lhs)
+            {
+                apply(idx, lhs.len(), rhs);
+            }
+        }
+        result
+    }
+
+    pub fn from_vec(rules: Vec<(Vec<SymbolIdx>, Vec<SymbolIdx>)>, symbol_set: SymbolSet) -> Self {
+        let mut rule_hash: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>> = HashMap::new();
+        //Should use a fancy map function here I admit
+        for i in &rules {
+            match rule_hash.get_mut(&i.0) {
+                Some(result_vec) => result_vec.push(i.1.clone()),
+                None => {
+                    rule_hash.insert(i.0.clone(), vec![i.1.clone()]);
+                }
+            }
+        }
+        Ruleset {
+            rules: rule_hash,
+            symbol_set: symbol_set,
+            max_input: rules.iter().max_by_key(|x| x.0.len()).unwrap().0.len(),
+            min_input: rules.iter().min_by_key(|x| x.0.len()).unwrap().0.len(),
+            anchored_rules: vec![],
+        }
+    }
+    //Lenient and panics on anything it can't make sense of, including too many symbols; see parse for proper diagnostics
+    pub fn from_string(input_str: &str) -> Self {
+        let mut rules_str: HashMap<Vec<&str>, Vec<Vec<&str>>> = HashMap::new();
+        let mut symbols_rep: Vec<String> = Vec::new();
+
+        //Add un-indexed list of rules
+        for line in input_str.split('\n') {
+            let uncommented_line = line.split('#').next().unwrap();
+            //If line is exclusively whitespace
+            if uncommented_line
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .is_empty()
+            {
+                continue;
+            }
+            let mut split_hs = uncommented_line.split("-");
+
+            //safe unwrap as there must be at least one section in the split
+            let lhs_raw = split_hs.next().unwrap();
+
+            //If there is no -, assume the lhs simply goes into nothing
+            let rhs_raw = match split_hs.next() {
+                Some(rhs) => rhs,
+                None => "",
+            };
+
+            //Remove any empty strings and collect rest of strings (divided by whitespace) into lhs & rhs
+            let lhs: Vec<_> = lhs_raw
+                .split_whitespace()
+                .filter(|&x| !x.is_empty())
+                .collect();
+            let rhs: Vec<_> = rhs_raw
+                .split_whitespace()
+                .filter(|&x| !x.is_empty())
+                .collect();
+
+            for str in &lhs {
+                if !symbols_rep.contains(&(*str).to_owned()) {
+                    symbols_rep.push((*str).to_owned());
+                }
+            }
+
+            for str in &rhs {
+                if !symbols_rep.contains(&(*str).to_owned()) {
+                    symbols_rep.push((*str).to_owned());
+                }
+            }
+
+            match rules_str.get_mut(&lhs) {
+                Some(rhs_list) => {
+                    rhs_list.push(rhs);
+                }
+                None => {
+                    rules_str.insert(lhs, vec![rhs]);
+                }
+            }
+        }
+        //Sort symbols according to rust's str system for consistency between dfa & ruleset
+        symbols_rep.sort();
+        assert!(
+            symbols_rep.len() <= MAX_SYMBOLS,
+            "SRS uses {} symbols, more than SymbolIdx can hold",
+            symbols_rep.len()
+        );
+
+        let sym_set = SymbolSet {
+            length: symbols_rep.len(),
+            representations: symbols_rep,
+        };
+
+        //Convert rules to indexed equivalents
+
+        let mut rules: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>> = HashMap::new();
+
+        for (key, val) in rules_str {
+            let mut rhs = vec![];
+            for result in val {
+                rhs.push(sym_set.string_to_symbols(&result).unwrap());
+            }
+            rules.insert(sym_set.string_to_symbols(&key).unwrap(), rhs);
+        }
+
+        Ruleset {
+            symbol_set: sym_set,
+            max_input: rules.keys().max_by_key(|x| x.len()).unwrap().len(),
+            min_input: rules.keys().min_by_key(|x| x.len()).unwrap().len(),
+            rules: rules,
+            anchored_rules: vec![],
+        }
+    }
+
+    //Strict version of from_string -- reports every malformed line instead of guessing or panicking.
+    //include directives are rejected, as there is nowhere to load them from; see parse_with_includes
+    pub fn parse(input_str: &str) -> Result<Self, Vec<RuleParseError>> {
+        Self::parse_with_includes(input_str, |_| None)
+    }
+
+    //Include directives are relative to the file they're in. resolve_include maps the path of an included file,
+    //relative to the directory of the top-level file, to the contents of that file
+    pub fn parse_with_includes<F>(
+        input_str: &str,
+        resolve_include: F,
+    ) -> Result<Self, Vec<RuleParseError>>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        Self::parse_from(input_str, None, resolve_include)
+    }
+
+    //top_level is the path of the file being parsed, if it has one, so that including it again is caught as a cycle
+    fn parse_from<F>(
+        input_str: &str,
+        top_level: Option<&Path>,
+        mut resolve_include: F,
+    ) -> Result<Self, Vec<RuleParseError>>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut errors = vec![];
+        let mut rules_str = vec![];
+        let mut classes = HashMap::new();
+        collect_rules(
+            input_str,
+            Path::new(""),
+            &mut resolve_include,
+            &mut top_level.map(normalize_path).into_iter().collect(),
+            &mut classes,
+            &mut rules_str,
+            &mut errors,
+        );
+
+        if rules_str.is_empty() && errors.is_empty() {
+            errors.push(RuleParseError {
+                line: 1,
+                column: 1,
+                kind: RuleParseErrorKind::EmptyFile,
+            });
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut symbols_rep: Vec<String> = Vec::new();
+        let mut seen = HashSet::new();
+        //Line of the rule that first went over the limit
+        let mut overflow_line = None;
+        for rule in &rules_str {
+            for str in rule.lhs.iter().chain(rule.rhs.iter()) {
+                if seen.insert(str) {
+                    symbols_rep.push(str.clone());
+                    if symbols_rep.len() > MAX_SYMBOLS && overflow_line.is_none() {
+                        overflow_line = Some(rule.line);
+                    }
+                }
+            }
+        }
+        if let Some(line) = overflow_line {
+            return Err(vec![RuleParseError {
+                line,
+                column: 1,
+                kind: RuleParseErrorKind::TooManySymbols(symbols_rep.len()),
+            }]);
+        }
+        let sym_set = SymbolSet::<String>::new(symbols_rep);
+        let mut rules: HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>> = HashMap::new();
+        let mut anchored_rules = vec![];
+        for rule in rules_str {
+            let lhs = rule.lhs.iter().map(|x| &x[..]).collect();
+            let rhs = rule.rhs.iter().map(|x| &x[..]).collect();
+            //Safe unwraps, every symbol was added to the symbol set above
+            let lhs = sym_set.string_to_symbols(&lhs).unwrap();
+            let rhs = sym_set.string_to_symbols(&rhs).unwrap();
+            //Rules pulled in from several files may overlap, which is harmless
+            match rule.anchor {
+                None => {
+                    let rhs_list = rules.entry(lhs).or_default();
+                    if !rhs_list.contains(&rhs) {
+                        rhs_list.push(rhs);
+                    }
+                }
+                Some(anchor) => {
+                    if !anchored_rules.contains(&(anchor, lhs.clone(), rhs.clone())) {
+                        anchored_rules.push((anchor, lhs, rhs));
+                    }
+                }
+            }
+        }
+        Ok(Ruleset::with_anchored_rules(rules, anchored_rules, sym_set))
+    }
+
+    //Parses an SRS file from disk, resolving includes relative to the directory of that file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parse_file(
+        path: &std::path::Path,
+    ) -> std::io::Result<Result<Self, Vec<RuleParseError>>> {
+        let contents = std::fs::read_to_string(path)?;
+        let base_dir = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let file_name = path.file_name().map(Path::new);
+        Ok(Self::parse_from(&contents, file_name, |include| {
+            std::fs::read_to_string(base_dir.join(include)).ok()
+        }))
+    }
+
+    pub fn expand_to_symset(&mut self, expanded_ss: SymbolSet) {
+        let mut translate_map = HashMap::new();
+        let mut expanded_idx = 0;
+        for (idx, rep) in self.symbol_set.representations.iter().enumerate() {
+            while rep != &expanded_ss.representations[expanded_idx] {
+                expanded_idx += 1;
+            }
+            translate_map.insert(idx as SymbolIdx, expanded_idx as SymbolIdx);
+        }
+        let mut new_rules = HashMap::new();
+        for (lhs, rhs) in self.rules.iter_mut() {
+            let mut new_lhs = lhs.clone();
+            new_lhs
+                .iter_mut()
+                .for_each(|x| *x = *translate_map.get(x).unwrap());
+            rhs.iter_mut().for_each(|i_vec| {
+                i_vec
+                    .iter_mut()
+                    .for_each(|x| *x = *translate_map.get(x).unwrap())
+            });
+            new_rules.insert(new_lhs, rhs.to_owned());
+        }
+        for (_, lhs, rhs) in self.anchored_rules.iter_mut() {
+            lhs.iter_mut()
+                .chain(rhs.iter_mut())
+                .for_each(|x| *x = *translate_map.get(x).unwrap());
+        }
+        self.rules = new_rules;
+        self.symbol_set = expanded_ss;
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut result = "".to_owned();
+        for rule in &self.rules {
+            //Map each element to its string rep then join all string reps together by a space.
+            let lhs = rule
+                .0
+                .iter()
+                .map(|&x| self.symbol_set.representations[x as usize].clone())
+                .collect::<Vec<String>>()
+                .join(" ");
+            for rhs_vec in rule.1 {
+                let rhs = rhs_vec
+                    .iter()
+                    .map(|&x| self.symbol_set.representations[x as usize].clone())
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                result.push_str(&lhs);
+                result.push_str(" - ");
+                result.push_str(&rhs);
+                result.push('\n');
+            }
+        }
+        for (anchor, lhs, rhs) in &self.anchored_rules {
+            let to_reps = |side: &Vec<SymbolIdx>| {
+                side.iter()
+                    .map(|&x| self.symbol_set.representations[x as usize].clone())
+                    .collect::<Vec<String>>()
+            };
+            let mut lhs_reps = to_reps(lhs);
+            if anchor.at_start() {
+                lhs_reps.insert(0, "^".to_owned());
+            }
+            if anchor.at_end() {
+                lhs_reps.push("$".to_owned());
+            }
+            result.push_str(&lhs_reps.join(" "));
+            result.push_str(" - ");
+            result.push_str(&to_reps(rhs).join(" "));
+            result.push('\n');
+        }
+        result
+    }
+    pub fn has_generating_rule(&self) -> Option<(Vec<SymbolIdx>, Vec<SymbolIdx>)> {
+        self.rule_pairs()
+            .find(|(lhs, rhs)| rhs.len() > lhs.len())
+            .map(|(lhs, rhs)| (lhs.clone(), rhs.clone()))
+    }
+    pub fn has_deleting_rule(&self) -> Option<(Vec<SymbolIdx>, Vec<SymbolIdx>)> {
+        self.rule_pairs()
+            .find(|(lhs, rhs)| rhs.len() < lhs.len())
+            .map(|(lhs, rhs)| (lhs.clone(), rhs.clone()))
+    }
+    pub fn has_non_length_preserving_rule(&self) -> Option<(Vec<SymbolIdx>, Vec<SymbolIdx>)> {
+        self.rule_pairs()
+            .find(|(lhs, rhs)| rhs.len() != lhs.len())
+            .map(|(lhs, rhs)| (lhs.clone(), rhs.clone()))
+    }
+    pub fn has_definitely_cyclic_rule(&self) -> Option<(Vec<SymbolIdx>, Vec<SymbolIdx>)> {
+        for rule in &self.rules {
+            for rhs_vec in rule.1 {
+                if let Some(rhs_as_lhs_rules) = self.rules.get(rhs_vec) {
+                    for rhs_from_rhs in rhs_as_lhs_rules {
+                        if rule.0 == rhs_from_rhs {
+                            return Some((rule.0.clone(), rhs_vec.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        for (anchor, lhs, rhs) in &self.anchored_rules {
+            if self
+                .anchored_rules
+                .contains(&(*anchor, rhs.clone(), lhs.clone()))
+            {
+                return Some((lhs.clone(), rhs.clone()));
+            }
+        }
+        None
+    }
+}
+
+//A rule as written in an SRS file, before symbols are indexed
+struct RawRule {
+    lhs: Vec<String>,
+    rhs: Vec<String>,
+    anchor: Option<Anchor>,
+    line: usize,
+}
+
+//Splits a set literal such as "{0,1,2}" or "{0 1 2}" into its symbols
+fn parse_set_literal(set: &str) -> Option<Vec<String>> {
+    let inner = set.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let symbols: Vec<String> = inner
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_owned())
+        .collect();
+    if symbols.is_empty() {
+        None
+    } else {
+        Some(symbols)
+    }
+}
+
+//Removes "." and resolves ".." where it can without touching the file system, so that different ways
+//of writing the same path compare equal
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+//Parses "x in {0,1}, y in peg" into its variables and the symbols each can take on
+fn parse_where_clause(
+    clause: &str,
+    classes: &HashMap<String, Vec<String>>,
+) -> Result<Vec<(String, Vec<String>)>, RuleParseErrorKind> {
+    let mut variables: Vec<(String, Vec<String>)> = vec![];
+    let mut rest = clause.trim();
+    while !rest.is_empty() {
+        //Step past the variable and "in" tokens themselves, since either could contain the other
+        let var_end = rest
+            .find(char::is_whitespace)
+            .ok_or(RuleParseErrorKind::InvalidWhereClause)?;
+        let var = &rest[..var_end];
+        rest = rest[var_end..]
+            .trim_start()
+            .strip_prefix("in")
+            .filter(|x| x.starts_with(char::is_whitespace))
+            .ok_or(RuleParseErrorKind::InvalidWhereClause)?
+            .trim_start();
+        let (values, remaining) = if rest.starts_with('{') {
+            let end = rest
+                .find('}')
+                .ok_or(RuleParseErrorKind::InvalidWhereClause)?;
+            let values =
+                parse_set_literal(&rest[..=end]).ok_or(RuleParseErrorKind::InvalidWhereClause)?;
+            (values, &rest[end + 1..])
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let class_name = rest[..end].trim();
+            match classes.get(class_name) {
+                Some(values) => (values.clone(), &rest[end..]),
+                None => return Err(RuleParseErrorKind::UndefinedClass(class_name.to_owned())),
+            }
+        };
+        if variables.iter().any(|(name, _)| name == var) {
+            return Err(RuleParseErrorKind::DuplicateVariable(var.to_owned()));
+        }
+        variables.push((var.to_owned(), values));
+        rest = remaining.trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+            if rest.is_empty() {
+                return Err(RuleParseErrorKind::InvalidWhereClause);
+            }
+        } else if !rest.is_empty() {
+            return Err(RuleParseErrorKind::InvalidWhereClause);
+        }
+    }
+    if variables.is_empty() {
+        return Err(RuleParseErrorKind::InvalidWhereClause);
+    }
+    Ok(variables)
+}
+
+//Every way of assigning a symbol to each variable, substituted into lhs & rhs
+fn expand_schema(
+    lhs: &[&str],
+    rhs: &[&str],
+    variables: &[(String, Vec<String>)],
+) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut result = vec![];
+    let mut choice = vec![0; variables.len()];
+    loop {
+        let substitute = |side: &[&str]| -> Vec<String> {
+            side.iter()
+                .map(
+                    |sym| match variables.iter().position(|(name, _)| name == sym) {
+                        Some(var_idx) => variables[var_idx].1[choice[var_idx]].clone(),
+                        None => (*sym).to_owned(),
+                    },
+                )
+                .collect()
+        };
+        result.push((substitute(lhs), substitute(rhs)));
+
+        //Odometer-style increment through all assignments
+        let mut rollover = choice.len();
+        while rollover > 0 && choice[rollover - 1] == variables[rollover - 1].1.len() - 1 {
+            choice[rollover - 1] = 0;
+            rollover -= 1;
+        }
+        if rollover == 0 {
+            break;
+        }
+        choice[rollover - 1] += 1;
+    }
+    result
+}
+
+//Reads every rule out of one SRS file (and, recursively, the files it includes).
+//Line numbers of included rules are those of the include directive, so that the top-level file stays the frame of reference.
+//dir is the directory of this file and include_stack the files being read, both relative to the top-level file's directory
+fn collect_rules<F>(
+    input_str: &str,
+    dir: &Path,
+    resolve_include: &mut F,
+    include_stack: &mut Vec<PathBuf>,
+    classes: &mut HashMap<String, Vec<String>>,
+    rules: &mut Vec<RawRule>,
+    errors: &mut Vec<RuleParseError>,
+) where
+    F: FnMut(&str) -> Option<String>,
+{
+    //Only rules from this file, used to detect duplicates
+    let first_rule = rules.len();
+    for (line_idx, line) in input_str.lines().enumerate() {
+        let line_num = line_idx + 1;
+        let uncommented_line = line.split('#').next().unwrap();
+        if uncommented_line.trim().is_empty() {
+            continue;
+        }
+        //Columns are counted in chars so that multi-byte symbols don't throw the position off
+        let rule_column = uncommented_line
+            .chars()
+            .position(|c| !c.is_whitespace())
+            .unwrap()
+            + 1;
+        let mut report = |kind| {
+            errors.push(RuleParseError {
+                line: line_num,
+                column: rule_column,
+                kind,
+            })
+        };
+        let trimmed = uncommented_line.trim();
+
+        //include "other.srs"
+        if let Some(include) = trimmed.strip_prefix("include ") {
+            let path = include.trim();
+            let path = match path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+                Some(path) => path.to_owned(),
+                None => {
+                    report(RuleParseErrorKind::InvalidInclude);
+                    continue;
+                }
+            };
+            let full_path = normalize_path(&dir.join(&path));
+            if include_stack.contains(&full_path) {
+                report(RuleParseErrorKind::IncludeCycle(path));
+                continue;
+            }
+            let contents = match resolve_include(&full_path.to_string_lossy()) {
+                Some(contents) => contents,
+                None => {
+                    report(RuleParseErrorKind::IncludeNotFound(path));
+                    continue;
+                }
+            };
+            let mut included_rules = vec![];
+            let mut included_errors = vec![];
+            let included_dir = full_path.parent().unwrap_or(Path::new("")).to_path_buf();
+            include_stack.push(full_path);
+            collect_rules(
+                &contents,
+                &included_dir,
+                resolve_include,
+                include_stack,
+                classes,
+                &mut included_rules,
+                &mut included_errors,
+            );
+            include_stack.pop();
+            if !included_errors.is_empty() {
+                report(RuleParseErrorKind::Include {
+                    path,
+                    errors: included_errors,
+                });
+                continue;
+            }
+            for mut rule in included_rules {
+                rule.line = line_num;
+                rules.push(rule);
+            }
+            continue;
+        }
+
+        //class peg = {1,2}
+        if let Some(class_def) = trimmed.strip_prefix("class ") {
+            match class_def.split_once('=') {
+                Some((name, set)) if name.split_whitespace().count() == 1 => {
+                    match parse_set_literal(set) {
+                        Some(values) => {
+                            classes.insert(name.trim().to_owned(), values);
+                        }
+                        None => report(RuleParseErrorKind::InvalidClass),
+                    }
+                }
+                _ => report(RuleParseErrorKind::InvalidClass),
+            }
+            continue;
+        }
+
+        let (rule_part, where_clause) = match uncommented_line.split_once(" where ") {
+            Some((rule_part, where_clause)) => (rule_part, Some(where_clause)),
+            None => (uncommented_line, None),
+        };
+
+        let mut split_hs = rule_part.split('-');
+        let lhs_raw = split_hs.next().unwrap();
+        let rhs_raw = split_hs.next().unwrap_or("");
+        if split_hs.next().is_some() {
+            //Point at the second separator, which is the first one that doesn't belong
+            let extra_column = uncommented_line
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '-')
+                .nth(1)
+                .unwrap()
+                .0
+                + 1;
+            errors.push(RuleParseError {
+                line: line_num,
+                column: extra_column,
+                kind: RuleParseErrorKind::ExtraSeparator,
+            });
+            continue;
+        }
+
+        let mut lhs: Vec<_> = lhs_raw.split_whitespace().collect();
+        let rhs: Vec<_> = rhs_raw.split_whitespace().collect();
+
+        //^ 1 1 0 $ - 0 0 1
+        let at_start = lhs.first() == Some(&"^");
+        if at_start {
+            lhs.remove(0);
+        }
+        let at_end = lhs.last() == Some(&"$");
+        if at_end {
+            lhs.pop();
+        }
+        if lhs.iter().chain(rhs.iter()).any(|x| *x == "^" || *x == "$") {
+            report(RuleParseErrorKind::MisplacedAnchor);
+            continue;
+        }
+        let anchor = match (at_start, at_end) {
+            (false, false) => None,
+            (true, false) => Some(Anchor::Start),
+            (false, true) => Some(Anchor::End),
+            (true, true) => Some(Anchor::Both),
+        };
+        //"^ $ - 1" only ever rewrites the empty string, so it's the one empty LHS that's fine
+        if lhs.is_empty() && anchor != Some(Anchor::Both) {
+            report(RuleParseErrorKind::EmptyLhs);
+            continue;
+        }
+
+        let expansions = match where_clause {
+            None => vec![(
+                lhs.iter().map(|x| (*x).to_owned()).collect(),
+                rhs.iter().map(|x| (*x).to_owned()).collect(),
+            )],
+            Some(clause) => match parse_where_clause(clause, classes) {
+                Ok(variables) => {
+                    if let Some((unused, _)) = variables
+                        .iter()
+                        .find(|(name, _)| !lhs.contains(&&name[..]) && !rhs.contains(&&name[..]))
+                    {
+                        report(RuleParseErrorKind::UnusedVariable(unused.clone()));
+                        continue;
+                    }
+                    expand_schema(&lhs, &rhs, &variables)
+                }
+                Err(kind) => {
+                    report(kind);
+                    continue;
+                }
+            },
+        };
+        //Rules generated by a schema are allowed to be no-ops or to repeat other rules -- they're just skipped
+        let from_schema = where_clause.is_some();
+
+        for (lhs, rhs) in expansions {
+            if lhs == rhs {
+                if !from_schema {
+                    report(RuleParseErrorKind::IdenticalSides);
+                }
+                continue;
+            }
+            if let Some(earlier) = rules[first_rule..]
+                .iter()
+                .find(|rule| rule.lhs == lhs && rule.rhs == rhs && rule.anchor == anchor)
+            {
+                if !from_schema {
+                    report(RuleParseErrorKind::DuplicateRule {
+                        first_line: earlier.line,
+                    });
+                }
+                continue;
+            }
+            rules.push(RawRule {
+                lhs,
+                rhs,
+                anchor,
+                line: line_num,
+            });
+        }
+    }
+}
+
+//JSON keys have to be strings, so the rule map is stored as a sorted list of (LHS, RHS list) pairs instead
+mod rule_map {
+    use super::*;
+
+    //Each LHS with every RHS it can become, as in Ruleset::rules
+    type RuleMap = HashMap<Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>>;
+
+    pub fn serialize<S: Serializer>(rules: &RuleMap, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut pairs: Vec<_> = rules.iter().collect();
+        pairs.sort();
+        serializer.collect_seq(pairs)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RuleMap, D::Error> {
+        let pairs = Vec::<(Vec<SymbolIdx>, Vec<Vec<SymbolIdx>>)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
@@ -0,0 +1,315 @@
+//Termination checks for rulesets.
+//A ruleset terminates if no string can be rewritten forever. Proving that is undecidable in general,
+//so this tries a few orderings that are cheap to check, and otherwise looks for a concrete cycle.
+//Proofs ignore anchors -- an anchored rule applies in fewer places than its unanchored version,
+//so if the unanchored system terminates, so does the anchored one.
+use std::collections::{HashMap, HashSet};
+
+use super::Ruleset;
+use crate::util::SymbolIdx;
+
+//Highest symbol height tried when looking for a match-bound
+const MAX_MATCH_BOUND: usize = 4;
+//Cap on the size of the automaton built for a match-bound, so hopeless systems give up quickly
+const MAX_MATCH_STATES: usize = 5000;
+//Rounds of weight adjustment before giving up on a weight function
+const MAX_WEIGHT_ROUNDS: usize = 1000;
+//Strings explored while looking for a cycle
+const MAX_CYCLE_SEARCH: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Certificate {
+    //Every rule either shortens the string, or keeps its length and makes it lexicographically smaller.
+    //Holds the symbol precedence used, listed from smallest to largest
+    LengthLex(Vec<SymbolIdx>),
+    //Every rule strictly lowers the sum of the weights of the string's symbols. Indexed by symbol
+    Weight(Vec<usize>),
+    //No symbol can be rewritten more than this many times over (see Geser, Hofbauer & Waldmann's match-bounds)
+    MatchBound(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    Terminating(Certificate),
+    //Each string rewrites to the next in one step, and the last rewrites back to the first
+    Cycle(Vec<Vec<SymbolIdx>>),
+    //Neither a proof nor a cycle was found
+    Unknown,
+}
+
+pub fn check_termination(rules: &Ruleset) -> Termination {
+    if let Some(certificate) = prove_termination(rules) {
+        return Termination::Terminating(certificate);
+    }
+    match find_cycle(rules) {
+        Some(cycle) => Termination::Cycle(cycle),
+        None => Termination::Unknown,
+    }
+}
+
+pub fn prove_termination(rules: &Ruleset) -> Option<Certificate> {
+    length_lex(rules)
+        .or_else(|| weight_function(rules))
+        .or_else(|| (1..=MAX_MATCH_BOUND).find_map(|bound| match_bound(rules, bound)))
+}
+
+//Checks whether the certificate actually proves the ruleset terminates
+pub fn verify(rules: &Ruleset, certificate: &Certificate) -> bool {
+    match certificate {
+        Certificate::LengthLex(precedence) => {
+            let mut rank = vec![usize::MAX; rules.symbol_set.length];
+            for (position, sym) in precedence.iter().enumerate() {
+                rank[*sym as usize] = position;
+            }
+            if rank.contains(&usize::MAX) {
+                return false;
+            }
+            rules.rule_pairs().all(|(lhs, rhs)| {
+                lhs.len() > rhs.len()
+                    || (lhs.len() == rhs.len()
+                        && lhs
+                            .iter()
+                            .map(|x| rank[*x as usize])
+                            .gt(rhs.iter().map(|x| rank[*x as usize])))
+            })
+        }
+        Certificate::Weight(weights) => {
+            weights.len() == rules.symbol_set.length
+                && rules.rule_pairs().all(|(lhs, rhs)| {
+                    lhs.iter().map(|x| weights[*x as usize]).sum::<usize>()
+                        > rhs.iter().map(|x| weights[*x as usize]).sum::<usize>()
+                })
+        }
+        Certificate::MatchBound(bound) => match_bound(rules, *bound).is_some(),
+    }
+}
+
+//Searches for a string that can be rewritten back into itself, starting from each LHS and each pair of them
+pub fn find_cycle(rules: &Ruleset) -> Option<Vec<Vec<SymbolIdx>>> {
+    let lhs_list: Vec<&Vec<SymbolIdx>> = rules.rule_pairs().map(|(lhs, _)| lhs).collect();
+    //Concatenated, and overlapped wherever the end of the first matches the start of the second
+    let overlaps = lhs_list.iter().flat_map(|first| {
+        lhs_list.iter().flat_map(move |second| {
+            (0..first.len().min(second.len()))
+                .filter(move |overlap| first[first.len() - overlap..] == second[..*overlap])
+                .map(move |overlap| {
+                    let mut start = first.to_vec();
+                    start.extend(&second[overlap..]);
+                    start
+                })
+        })
+    });
+    let starts = lhs_list.iter().map(|x| x.to_vec()).chain(overlaps);
+    //Generating rules could otherwise keep the search going forever
+    let max_len = lhs_list.iter().map(|x| x.len()).max().unwrap_or(0) * 4;
+    //Strings that have been fully explored without finding a cycle
+    let mut finished = HashSet::new();
+    let mut explored = 0;
+    for start in starts {
+        if finished.contains(&start) {
+            continue;
+        }
+        explored += 1;
+        if explored > MAX_CYCLE_SEARCH {
+            return None;
+        }
+        //Depth-first, keeping the path so far so a cycle can be read straight off of it
+        let mut path = vec![start.clone()];
+        let mut on_path = HashMap::from([(start.clone(), 0)]);
+        let mut pending = vec![rules.rewrites(&start)];
+        while let Some(options) = pending.last_mut() {
+            match options.pop() {
+                Some(next) => {
+                    if let Some(idx) = on_path.get(&next) {
+                        return Some(path[*idx..].to_vec());
+                    }
+                    if finished.contains(&next) || next.len() > max_len {
+                        continue;
+                    }
+                    explored += 1;
+                    if explored > MAX_CYCLE_SEARCH {
+                        return None;
+                    }
+                    on_path.insert(next.clone(), path.len());
+                    pending.push(rules.rewrites(&next));
+                    path.push(next);
+                }
+                None => {
+                    pending.pop();
+                    let done = path.pop().unwrap();
+                    on_path.remove(&done);
+                    finished.insert(done);
+                }
+            }
+        }
+    }
+    None
+}
+
+//Length first, then lexicographic under some symbol precedence.
+//Each length-preserving rule forces its first differing LHS symbol above the RHS one; any topological order works
+fn length_lex(rules: &Ruleset) -> Option<Certificate> {
+    let mut above = vec![HashSet::new(); rules.symbol_set.length];
+    for (lhs, rhs) in rules.rule_pairs() {
+        if lhs.len() > rhs.len() {
+            continue;
+        }
+        if lhs.len() < rhs.len() {
+            return None;
+        }
+        let idx = lhs.iter().zip(rhs).position(|(l, r)| l != r)?;
+        above[lhs[idx] as usize].insert(rhs[idx]);
+    }
+    let mut precedence = vec![];
+    let mut placed = vec![false; rules.symbol_set.length];
+    while precedence.len() < rules.symbol_set.length {
+        //Smallest remaining symbol: one that doesn't need to beat anything not yet placed
+        let next = (0..rules.symbol_set.length)
+            .find(|sym| !placed[*sym] && above[*sym].iter().all(|x| placed[*x as usize]))?;
+        placed[next] = true;
+        precedence.push(next as SymbolIdx);
+    }
+    Some(Certificate::LengthLex(precedence))
+}
+
+//Looks for a weight per symbol such that every rule lowers the total weight.
+//Each rule's symbol count difference needs a positive dot product with the weights, so this is a perceptron
+fn weight_function(rules: &Ruleset) -> Option<Certificate> {
+    let sym_count = rules.symbol_set.length;
+    let differences: Vec<Vec<i64>> = rules
+        .rule_pairs()
+        .map(|(lhs, rhs)| {
+            let mut difference = vec![0; sym_count];
+            lhs.iter().for_each(|x| difference[*x as usize] += 1);
+            rhs.iter().for_each(|x| difference[*x as usize] -= 1);
+            difference
+        })
+        .collect();
+    let dot = |weights: &Vec<i64>, difference: &Vec<i64>| {
+        weights
+            .iter()
+            .zip(difference)
+            .map(|(w, d)| w * d)
+            .sum::<i64>()
+    };
+    let mut weights = vec![1; sym_count];
+    for _ in 0..MAX_WEIGHT_ROUNDS {
+        let mut changed = false;
+        for difference in &differences {
+            if dot(&weights, difference) <= 0 {
+                weights
+                    .iter_mut()
+                    .zip(difference)
+                    .for_each(|(w, d)| *w += d);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    //Weights can't be negative, but raising every weight by the same amount never hurts a rule that doesn't generate
+    let lowest = *weights.iter().min()?;
+    if lowest < 0 && rules.has_generating_rule().is_none() {
+        weights.iter_mut().for_each(|w| *w -= lowest);
+    }
+    if weights.iter().any(|w| *w < 0) || differences.iter().any(|d| dot(&weights, d) <= 0) {
+        return None;
+    }
+    Some(Certificate::Weight(
+        weights.into_iter().map(|w| w as usize).collect(),
+    ))
+}
+
+//Each symbol gets a height, starting at 0. Rewritten symbols get one more than the lowest height they replaced.
+//If heights can never pass the bound, the ruleset terminates. This builds an automaton accepting every string
+//of heighted symbols reachable from the unheighted ones, and fails if it needs a height above the bound.
+fn match_bound(rules: &Ruleset, bound: usize) -> Option<Certificate> {
+    let pairs: Vec<_> = rules.rule_pairs().collect();
+    //Empty sides would need empty transitions
+    if pairs
+        .iter()
+        .any(|(lhs, rhs)| lhs.is_empty() || rhs.is_empty())
+    {
+        return None;
+    }
+    //Transitions on (symbol, height). State 0 starts and accepts, and reads any unheighted string
+    let mut transitions: Vec<HashMap<(SymbolIdx, usize), Vec<usize>>> = vec![HashMap::new()];
+    for sym in 0..rules.symbol_set.length {
+        transitions[0].insert((sym as SymbolIdx, 0), vec![0]);
+    }
+    //States in the middle of an added RHS path, shared by every path for the same rule, height and position.
+    //Sharing lets the automaton accept more than it strictly needs to, but keeps it finite
+    let mut path_states: HashMap<(usize, usize, usize), usize> = HashMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for origin in 0..transitions.len() {
+            for (rule_idx, (lhs, rhs)) in pairs.iter().enumerate() {
+                //Every state reading lhs from origin can reach, with the lowest height read along the way
+                let mut reached = vec![(origin, usize::MAX)];
+                for sym in lhs.iter() {
+                    let mut next_reached = vec![];
+                    for (state, lowest) in &reached {
+                        for ((edge_sym, height), targets) in &transitions[*state] {
+                            if edge_sym == sym {
+                                for target in targets {
+                                    next_reached.push((*target, (*lowest).min(*height)));
+                                }
+                            }
+                        }
+                    }
+                    next_reached.sort();
+                    next_reached.dedup();
+                    reached = next_reached;
+                }
+                for (target, lowest) in reached {
+                    let height = lowest + 1;
+                    if height > bound {
+                        return None;
+                    }
+                    //Is there already a path reading rhs at this height?
+                    let mut states = vec![origin];
+                    for sym in rhs.iter() {
+                        states = states
+                            .iter()
+                            .flat_map(|x| {
+                                transitions[*x].get(&(*sym, height)).into_iter().flatten()
+                            })
+                            .copied()
+                            .collect();
+                        states.sort();
+                        states.dedup();
+                    }
+                    if states.contains(&target) {
+                        continue;
+                    }
+                    //If not, add one
+                    let mut state = origin;
+                    for (idx, sym) in rhs.iter().enumerate() {
+                        let next_state = if idx == rhs.len() - 1 {
+                            target
+                        } else {
+                            *path_states
+                                .entry((rule_idx, height, idx))
+                                .or_insert_with(|| {
+                                    transitions.push(HashMap::new());
+                                    transitions.len() - 1
+                                })
+                        };
+                        let targets = transitions[state].entry((*sym, height)).or_default();
+                        if !targets.contains(&next_state) {
+                            targets.push(next_state);
+                        }
+                        state = next_state;
+                    }
+                    if transitions.len() > MAX_MATCH_STATES {
+                        return None;
+                    }
+                    changed = true;
+                }
+            }
+        }
+    }
+    Some(Certificate::MatchBound(bound))
+}
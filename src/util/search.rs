@@ -0,0 +1,46 @@
+//Breadth first search over states, shared by everything that asks which states can reach which
+use std::collections::VecDeque;
+
+//The state each state was first reached from, searching breadth first from the starts (which are
+//their own parents). None for states that can't be reached
+pub(super) fn parents<I>(
+    state_count: usize,
+    starts: impl IntoIterator<Item = usize>,
+    next: impl Fn(usize) -> I,
+) -> Vec<Option<usize>>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut parents = vec![None; state_count];
+    let mut queue = VecDeque::new();
+    for state in starts {
+        if parents[state].is_none() {
+            parents[state] = Some(state);
+            queue.push_back(state);
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        for target in next(state) {
+            if parents[target].is_none() {
+                parents[target] = Some(state);
+                queue.push_back(target);
+            }
+        }
+    }
+    parents
+}
+
+//Whether each state can be reached from the starts
+pub(super) fn reachable<I>(
+    state_count: usize,
+    starts: impl IntoIterator<Item = usize>,
+    next: impl Fn(usize) -> I,
+) -> Vec<bool>
+where
+    I: IntoIterator<Item = usize>,
+{
+    parents(state_count, starts, next)
+        .iter()
+        .map(Option::is_some)
+        .collect()
+}
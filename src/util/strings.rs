@@ -0,0 +1,178 @@
+//Listing and drawing strings from a DFA's language, e.g. to make test boards.
+//Rejected strings are handled by doing the same with the complement
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+
+use super::{SymbolIdx, DFA};
+
+//Every accepted string of a DFA, shortest first and in symbol order within a length.
+//Ends if only finitely many strings are accepted, and otherwise goes on forever
+pub struct ShortlexStrings {
+    dfa: DFA,
+    //live[r][state] is whether some string of length r takes state to an accepting state
+    live: Vec<Vec<bool>>,
+    length: usize,
+    //No string longer than this is accepted, if only finitely many are
+    max_length: Option<usize>,
+    //The last string given, and the states it passes through (including the starting state)
+    string: Vec<SymbolIdx>,
+    path: Vec<usize>,
+    //Whether a string of the current length has been given yet
+    started_length: bool,
+}
+
+impl ShortlexStrings {
+    fn new(dfa: DFA) -> Self {
+        let max_length = if dfa.is_finite() {
+            //A longer string would repeat a state along its way, so pumping it would give infinitely many
+            Some(dfa.state_transitions.len())
+        } else {
+            None
+        };
+        ShortlexStrings {
+            live: vec![dfa.accepting_states.clone()],
+            path: vec![dfa.starting_state],
+            dfa,
+            length: 0,
+            max_length,
+            string: vec![],
+            started_length: false,
+        }
+    }
+
+    fn extend_live(&mut self) {
+        while self.live.len() <= self.length {
+            let previous = self.live.last().unwrap();
+            let next = self
+                .dfa
+                .state_transitions
+                .iter()
+                .map(|x| x.iter().any(|target| previous[*target]))
+                .collect();
+            self.live.push(next);
+        }
+    }
+
+    //Completes the string from this position with the smallest symbols that can still be accepted.
+    //The state at this position must be able to reach an accepting state in the remaining length
+    fn fill_from(&mut self, position: usize) {
+        for i in position..self.length {
+            let remaining = &self.live[self.length - i - 1];
+            let transitions = &self.dfa.state_transitions[self.path[i]];
+            let symbol = (0..transitions.len())
+                .find(|x| remaining[transitions[*x]])
+                .unwrap();
+            self.string.push(symbol as SymbolIdx);
+            self.path.push(transitions[symbol]);
+        }
+    }
+
+    //Moves to the next accepted string of the same length, if there is one
+    fn advance(&mut self) -> bool {
+        for i in (0..self.length).rev() {
+            let previous = self.string[i] as usize;
+            self.string.truncate(i);
+            self.path.truncate(i + 1);
+            let remaining = &self.live[self.length - i - 1];
+            let transitions = &self.dfa.state_transitions[self.path[i]];
+            if let Some(symbol) =
+                (previous + 1..transitions.len()).find(|x| remaining[transitions[*x]])
+            {
+                self.string.push(symbol as SymbolIdx);
+                self.path.push(transitions[symbol]);
+                self.fill_from(i + 1);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for ShortlexStrings {
+    type Item = Vec<SymbolIdx>;
+
+    fn next(&mut self) -> Option<Vec<SymbolIdx>> {
+        loop {
+            if self.max_length.is_some_and(|x| self.length > x) {
+                return None;
+            }
+            self.extend_live();
+            let found = if self.started_length {
+                self.advance()
+            } else {
+                self.started_length = true;
+                let possible = self.live[self.length][self.dfa.starting_state];
+                if possible {
+                    self.fill_from(0);
+                }
+                possible
+            };
+            if found {
+                return Some(self.string.clone());
+            }
+            self.length += 1;
+            self.string.clear();
+            self.path.truncate(1);
+            self.started_length = false;
+        }
+    }
+}
+
+impl DFA {
+    pub fn accepted_strings(&self) -> ShortlexStrings {
+        ShortlexStrings::new(self.clone())
+    }
+
+    pub fn rejected_strings(&self) -> ShortlexStrings {
+        ShortlexStrings::new(!self)
+    }
+
+    //A uniformly random accepted string of this length, or None if none are accepted.
+    //Each symbol is picked with probability proportional to how many accepted strings continue with it
+    pub fn sample_accepted<R: Rng + ?Sized>(
+        &self,
+        length: usize,
+        rng: &mut R,
+    ) -> Option<Vec<SymbolIdx>> {
+        //ways[r][state] is how many strings of length r take state to an accepting state
+        let mut ways = vec![self
+            .accepting_states
+            .iter()
+            .map(|x| BigUint::from(*x as u8))
+            .collect::<Vec<_>>()];
+        for _ in 0..length {
+            let previous = ways.last().unwrap();
+            let next = self
+                .state_transitions
+                .iter()
+                .map(|x| x.iter().map(|target| &previous[*target]).sum())
+                .collect();
+            ways.push(next);
+        }
+        if ways[length][self.starting_state] == BigUint::default() {
+            return None;
+        }
+        let mut state = self.starting_state;
+        let mut string = Vec::with_capacity(length);
+        for remaining in (0..length).rev() {
+            let mut choice = rng.gen_biguint_below(&ways[remaining + 1][state]);
+            for (symbol, target) in self.state_transitions[state].iter().enumerate() {
+                if choice < ways[remaining][*target] {
+                    string.push(symbol as SymbolIdx);
+                    state = *target;
+                    break;
+                }
+                choice -= &ways[remaining][*target];
+            }
+        }
+        Some(string)
+    }
+
+    pub fn sample_rejected<R: Rng + ?Sized>(
+        &self,
+        length: usize,
+        rng: &mut R,
+    ) -> Option<Vec<SymbolIdx>> {
+        (!self).sample_accepted(length, rng)
+    }
+}
@@ -0,0 +1,165 @@
+//Symmetries of a ruleset and goal pair.
+//A symmetry is a way of relabelling symbols (and possibly reading strings back to front) that sends every rule
+//to a rule and the goal language to itself. A string is then solvable exactly when its image is,
+//which lets solvers work out one string and get the others in its orbit for free.
+use std::collections::HashSet;
+
+use super::{Anchor, Ruleset, SymbolIdx, DFA};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symmetry {
+    //What each symbol becomes, indexed by symbol
+    pub permutation: Vec<SymbolIdx>,
+    //Whether strings are also reversed
+    pub reversed: bool,
+}
+
+impl Symmetry {
+    pub fn apply(&self, string: &[SymbolIdx]) -> Vec<SymbolIdx> {
+        let mut result: Vec<SymbolIdx> = string
+            .iter()
+            .map(|x| self.permutation[*x as usize])
+            .collect();
+        if self.reversed {
+            result.reverse();
+        }
+        result
+    }
+
+    pub fn is_identity(&self) -> bool {
+        !self.reversed
+            && self
+                .permutation
+                .iter()
+                .enumerate()
+                .all(|(idx, x)| *x as usize == idx)
+    }
+
+    //Every symmetry of the pair other than the identity, sorted. Together with the identity these form a group,
+    //so the inverse of each one is also in the list.
+    //Assumes the goal uses the ruleset's symbol indices (as it does once a solver has been built)
+    pub fn find_all(rules: &Ruleset, goal: &DFA) -> Vec<Symmetry> {
+        let sym_count = rules.symbol_set.length;
+        if goal.symbol_set.length != sym_count {
+            return vec![];
+        }
+        //Not rule_pairs, which would also list each anchored rule as unanchored
+        let rule_set: HashSet<(Option<Anchor>, Vec<SymbolIdx>, Vec<SymbolIdx>)> = rules
+            .rules
+            .iter()
+            .flat_map(|(lhs, rhs_list)| {
+                rhs_list
+                    .iter()
+                    .map(move |rhs| (None, lhs.clone(), rhs.clone()))
+            })
+            .chain(
+                rules
+                    .anchored_rules
+                    .iter()
+                    .map(|(anchor, lhs, rhs)| (Some(*anchor), lhs.clone(), rhs.clone())),
+            )
+            .collect();
+        //Rules grouped by their highest symbol, so each can be checked as soon as every symbol in it has an image
+        let mut by_highest = vec![vec![]; sym_count];
+        for rule in &rule_set {
+            if let Some(highest) = rule.1.iter().chain(&rule.2).max() {
+                by_highest[*highest as usize].push(rule);
+            }
+        }
+        let reversed_goal = goal.reverse();
+        let goal_signatures = symbol_signatures(goal);
+        let reversed_signatures = symbol_signatures(&reversed_goal);
+        let mut result = vec![];
+        for reversed in [false, true] {
+            let base_signatures = if reversed {
+                &reversed_signatures
+            } else {
+                &goal_signatures
+            };
+            //Depth-first over partial permutations, assigning images to symbols in order
+            let mut stack = vec![vec![]];
+            while let Some(partial) = stack.pop() {
+                if partial.len() == sym_count {
+                    let symmetry = Symmetry {
+                        permutation: partial,
+                        reversed,
+                    };
+                    let base_goal = if reversed { &reversed_goal } else { goal };
+                    if !symmetry.is_identity() && symmetry.permute(base_goal) == *goal {
+                        result.push(symmetry);
+                    }
+                    continue;
+                }
+                for image in (0..sym_count).map(|x| x as SymbolIdx) {
+                    if partial.contains(&image)
+                        || base_signatures[partial.len()] != goal_signatures[image as usize]
+                    {
+                        continue;
+                    }
+                    let mut permutation = partial.clone();
+                    permutation.push(image);
+                    let candidate = Symmetry {
+                        permutation,
+                        reversed,
+                    };
+                    if by_highest[partial.len()]
+                        .iter()
+                        .all(|rule| rule_set.contains(&candidate.apply_rule(rule)))
+                    {
+                        stack.push(candidate.permutation);
+                    }
+                }
+            }
+        }
+        result.sort();
+        result
+    }
+
+    fn apply_rule(
+        &self,
+        (anchor, lhs, rhs): &(Option<Anchor>, Vec<SymbolIdx>, Vec<SymbolIdx>),
+    ) -> (Option<Anchor>, Vec<SymbolIdx>, Vec<SymbolIdx>) {
+        let anchor = match anchor {
+            Some(Anchor::Start) if self.reversed => Some(Anchor::End),
+            Some(Anchor::End) if self.reversed => Some(Anchor::Start),
+            _ => *anchor,
+        };
+        (anchor, self.apply(lhs), self.apply(rhs))
+    }
+
+    //Relabels the DFA's transitions, so it accepts the images of what it used to accept (ignoring reversal)
+    fn permute(&self, dfa: &DFA) -> DFA {
+        let mut permuted = dfa.clone();
+        for (state, transitions) in dfa.state_transitions.iter().enumerate() {
+            for (symbol, target) in transitions.iter().enumerate() {
+                permuted.state_transitions[state][self.permutation[symbol] as usize] = *target;
+            }
+        }
+        permuted
+    }
+}
+
+//Facts about how each symbol acts on the minimal DFA: self loops, transitions into accepting states and distinct targets.
+//A symmetry has to send each symbol to one that acts the same way on the goal, which rules out most candidates early
+//even for symbols no rule mentions
+fn symbol_signatures(dfa: &DFA) -> Vec<(usize, usize, usize)> {
+    let mut minimal = dfa.clone();
+    minimal.minimize();
+    minimal.trim_unreachable();
+    (0..minimal.symbol_set.length)
+        .map(|sym| {
+            let targets: Vec<usize> = minimal.state_transitions.iter().map(|x| x[sym]).collect();
+            let self_loops = targets
+                .iter()
+                .enumerate()
+                .filter(|(state, target)| state == *target)
+                .count();
+            let into_accepting = targets
+                .iter()
+                .filter(|x| minimal.accepting_states[**x])
+                .count();
+            let distinct = targets.iter().collect::<HashSet<_>>().len();
+            (self_loops, into_accepting, distinct)
+        })
+        .collect()
+}
@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
+//Symbols are single bytes unless the wide_symbols feature is on, which allows alphabets of more than 256 symbols
+#[cfg(not(feature = "wide_symbols"))]
 pub type SymbolIdx = u8;
+#[cfg(feature = "wide_symbols")]
+pub type SymbolIdx = u16;
+//The most symbols a symbol set can have
+pub const MAX_SYMBOLS: usize = SymbolIdx::MAX as usize + 1;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct SymbolSet<PrettyInput = String> {
@@ -23,7 +29,7 @@ impl<'a, PrettyInput> Iterator for SymbolSetIter<'a, PrettyInput> {
             Some(self.cur_vec.clone())
         };
         let mut rollover = self.cur_vec.len();
-        while rollover > 0 && self.cur_vec[rollover - 1] == (self.symset.length - 1) as u8 {
+        while rollover > 0 && self.cur_vec[rollover - 1] == (self.symset.length - 1) as SymbolIdx {
             self.cur_vec[rollover - 1] = 0;
             rollover -= 1;
         }
@@ -118,7 +124,7 @@ impl<PrettyInput> SymbolSet<PrettyInput> {
         let mut new_index = 1;
         for _ in 0..k {
             for i in start_index..end_index {
-                for symbol in 0..(self.length as SymbolIdx) {
+                for symbol in (0..self.length).map(|x| x as SymbolIdx) {
                     signature_set.push(signature_set[i].clone());
                     signature_set[new_index].push(symbol);
                     new_index += 1;
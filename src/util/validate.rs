@@ -0,0 +1,189 @@
+//Checking that a DFA's tables fit together, and tidying up its states.
+//DFAs are checked when they're deserialised, so a broken file fails to load rather than panicking in a solver later
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::{search, SymbolSet, DFA, MAX_SYMBOLS};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DfaError {
+    //The DFA has no states at all, so not even a starting state
+    NoStates,
+    //The symbol set's length doesn't match how many symbols it lists. Holds both
+    SymbolSetLength(usize, usize),
+    //More symbols than SymbolIdx can index
+    TooManySymbols(usize),
+    //The starting state doesn't exist
+    StartingStateOutOfRange(usize),
+    //accepting_states doesn't have one entry per state. Holds its length
+    AcceptingStatesLength(usize),
+    //This state's row of transitions doesn't have one entry per symbol
+    WrongRowLength(usize),
+    //The transition from this state on this symbol goes to a state that doesn't exist
+    TransitionOutOfRange(usize, usize),
+}
+
+impl fmt::Display for DfaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DfaError::NoStates => write!(f, "DFA has no states"),
+            DfaError::SymbolSetLength(length, listed) => write!(
+                f,
+                "symbol set has length {} but lists {} symbols",
+                length, listed
+            ),
+            DfaError::TooManySymbols(count) => write!(
+                f,
+                "{} symbols, at most {} are supported",
+                count, MAX_SYMBOLS
+            ),
+            DfaError::StartingStateOutOfRange(state) => {
+                write!(f, "starting state {} doesn't exist", state)
+            }
+            DfaError::AcceptingStatesLength(length) => write!(
+                f,
+                "accepting states has {} entries, not one per state",
+                length
+            ),
+            DfaError::WrongRowLength(state) => write!(
+                f,
+                "state {} doesn't have exactly one transition per symbol",
+                state
+            ),
+            DfaError::TransitionOutOfRange(state, symbol) => write!(
+                f,
+                "transition from state {} on symbol {} goes to a state that doesn't exist",
+                state, symbol
+            ),
+        }
+    }
+}
+
+//What a DFA deserialises as, before it's been checked
+#[derive(Deserialize)]
+pub(crate) struct UncheckedDFA<I, O> {
+    starting_state: usize,
+    state_transitions: Vec<Vec<usize>>,
+    accepting_states: Vec<O>,
+    symbol_set: SymbolSet<I>,
+}
+
+impl<I, O> TryFrom<UncheckedDFA<I, O>> for DFA<I, O> {
+    type Error = DfaError;
+
+    fn try_from(unchecked: UncheckedDFA<I, O>) -> Result<Self, DfaError> {
+        let dfa = DFA {
+            starting_state: unchecked.starting_state,
+            state_transitions: unchecked.state_transitions,
+            accepting_states: unchecked.accepting_states,
+            symbol_set: unchecked.symbol_set,
+        };
+        dfa.validate()?;
+        Ok(dfa)
+    }
+}
+
+impl<I, O> DFA<I, O> {
+    pub fn validate(&self) -> Result<(), DfaError> {
+        let state_count = self.state_transitions.len();
+        let symbol_count = self.symbol_set.length;
+        if state_count == 0 {
+            return Err(DfaError::NoStates);
+        }
+        if symbol_count != self.symbol_set.representations.len() {
+            return Err(DfaError::SymbolSetLength(
+                symbol_count,
+                self.symbol_set.representations.len(),
+            ));
+        }
+        if symbol_count > MAX_SYMBOLS {
+            return Err(DfaError::TooManySymbols(symbol_count));
+        }
+        if self.starting_state >= state_count {
+            return Err(DfaError::StartingStateOutOfRange(self.starting_state));
+        }
+        if self.accepting_states.len() != state_count {
+            return Err(DfaError::AcceptingStatesLength(self.accepting_states.len()));
+        }
+        for (state, row) in self.state_transitions.iter().enumerate() {
+            if row.len() != symbol_count {
+                return Err(DfaError::WrongRowLength(state));
+            }
+            if let Some(symbol) = row.iter().position(|x| *x >= state_count) {
+                return Err(DfaError::TransitionOutOfRange(state, symbol));
+            }
+        }
+        Ok(())
+    }
+
+    //Removes the states that no string reaches, keeping the rest in their original order
+    pub fn trim_unreachable(&mut self) {
+        let state_count = self.state_transitions.len();
+        let reachable = search::reachable(state_count, [self.starting_state], |state| {
+            self.state_transitions[state].iter().copied()
+        });
+        if reachable.iter().all(|x| *x) {
+            return;
+        }
+
+        let mut new_idx = vec![usize::MAX; state_count];
+        for (new, state) in (0..state_count).filter(|x| reachable[*x]).enumerate() {
+            new_idx[state] = new;
+        }
+        self.state_transitions = std::mem::take(&mut self.state_transitions)
+            .into_iter()
+            .zip(&reachable)
+            .filter(|x| *x.1)
+            .map(|(row, _)| row.into_iter().map(|x| new_idx[x]).collect())
+            .collect();
+        self.accepting_states = std::mem::take(&mut self.accepting_states)
+            .into_iter()
+            .zip(&reachable)
+            .filter(|x| *x.1)
+            .map(|x| x.0)
+            .collect();
+        self.starting_state = new_idx[self.starting_state];
+    }
+}
+
+impl DFA {
+    //Non-accepting states that can't be left, so every string reaching one is rejected
+    pub fn sink_states(&self) -> Vec<usize> {
+        (0..self.state_transitions.len())
+            .filter(|x| {
+                !self.accepting_states[*x] && self.state_transitions[*x].iter().all(|y| y == x)
+            })
+            .collect()
+    }
+
+    //Sends every missing transition (usize::MAX, or past the end of a short row) to a sink state.
+    //Uses a state that's already a sink once completed if there is one, and otherwise adds one.
+    //Returns the sink, or None if nothing was missing
+    pub fn complete_with_sink(&mut self) -> Option<usize> {
+        let symbol_count = self.symbol_set.length;
+        let missing = |row: &Vec<usize>| row.len() < symbol_count || row.contains(&usize::MAX);
+        if !self.state_transitions.iter().any(missing) {
+            return None;
+        }
+        let sink = (0..self.state_transitions.len())
+            .find(|x| {
+                !self.accepting_states[*x]
+                    && self.state_transitions[*x]
+                        .iter()
+                        .all(|y| y == x || *y == usize::MAX)
+            })
+            .unwrap_or_else(|| {
+                self.state_transitions.push(vec![]);
+                self.accepting_states.push(false);
+                self.state_transitions.len() - 1
+            });
+        for row in &mut self.state_transitions {
+            row.resize(symbol_count, usize::MAX);
+            for target in row.iter_mut().filter(|x| **x == usize::MAX) {
+                *target = sink;
+            }
+        }
+        Some(sink)
+    }
+}
@@ -1,11 +1,26 @@
 use std::any::type_name;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
 
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use srs_to_dfa::builder::tracks::{lift_goal, lift_goal_each_row, lift_ruleset, Move};
 use srs_to_dfa::builder::*;
+use srs_to_dfa::project::{Project, SolverType};
 use srs_to_dfa::solver::*;
 use srs_to_dfa::test::*;
-use srs_to_dfa::util::Ruleset;
-use srs_to_dfa::util::DFA;
+use srs_to_dfa::util::completion::{CompletionBudget, ReductionOrder};
+use srs_to_dfa::util::confluence::CriticalPair;
+use srs_to_dfa::util::termination::{check_termination, verify, Certificate, Termination};
+use srs_to_dfa::util::{
+    Anchor, RegexParseError, RegexParseErrorKind, RuleParseError, RuleParseErrorKind, Ruleset,
+    SymbolIdx, SymbolSet, Symmetry,
+};
+use srs_to_dfa::util::{BinaryDFAError, DFAView, DfaError, DotOptions, JflapError, DFA, NFA};
 
 #[test]
 #[ignore = "expensive"]
@@ -98,6 +113,1526 @@ fn ruleset_parsing() {
     );
 }
 
+#[test]
+fn ruleset_strict_parsing() {
+    let solve_test = build_threerulesolver::<MinkidSolver>().unwrap();
+    assert!(
+        solve_test.get_ruleset() == &Ruleset::parse(&solve_test.get_ruleset().to_string()).unwrap(),
+        "threerulesolver ruleset failed to recreate itself"
+    );
+
+    let errors = Ruleset::parse("# nothing but a comment\n\n").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, RuleParseErrorKind::EmptyFile);
+
+    let errors = Ruleset::parse("1 1 0 - 0 0 1\n0 1 - 1 0 - 1\n 1 0 - 1 0\n1 1 0 - 0 0 1 # again")
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            RuleParseError {
+                line: 2,
+                column: 11,
+                kind: RuleParseErrorKind::ExtraSeparator
+            },
+            RuleParseError {
+                line: 3,
+                column: 2,
+                kind: RuleParseErrorKind::IdenticalSides
+            },
+            RuleParseError {
+                line: 4,
+                column: 1,
+                kind: RuleParseErrorKind::DuplicateRule { first_line: 1 }
+            },
+        ]
+    );
+
+    let errors = Ruleset::parse(" - 1\n0 1 - 1\n^ - 0").unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            RuleParseError {
+                line: 1,
+                column: 2,
+                kind: RuleParseErrorKind::EmptyLhs
+            },
+            RuleParseError {
+                line: 3,
+                column: 1,
+                kind: RuleParseErrorKind::EmptyLhs
+            },
+        ]
+    );
+}
+
+#[test]
+fn ruleset_schemas() {
+    let schema_srs = "class peg = {1, 2}
+        x 1 0 - 0 0 x where x in peg
+        0 1 x - x 0 0 where x in peg
+        x 0 1 - 0 x 0 where x in {1,2}
+        1 0 2 - 0 2 0";
+    assert!(
+        Ruleset::parse(schema_srs).unwrap() == build_threerulesolver_rs(),
+        "schemas failed to expand to threerulesolver"
+    );
+
+    //Variable names may contain "in"
+    assert!(
+        Ruleset::parse(&schema_srs.replace('x', "pin")).unwrap() == build_threerulesolver_rs(),
+        "schemas with a variable containing \"in\" failed to expand"
+    );
+
+    //Assignments where both sides match are dropped rather than reported
+    let swaps = Ruleset::parse("x y - y x where x in {0,1}, y in {0,1}").unwrap();
+    assert_eq!(swaps.rules.len(), 2);
+
+    let files = std::collections::HashMap::from([
+        ("peg.srs", "1 1 0 - 0 0 1\ninclude \"rev.srs\""),
+        ("rev.srs", "0 1 1 - 1 0 0"),
+        ("loop.srs", "include \"loop.srs\""),
+        ("rules/peg.srs", "1 1 0 - 0 0 1\ninclude \"rev.srs\""),
+        ("rules/rev.srs", "0 1 1 - 1 0 0"),
+        ("rules/loop.srs", "include \"./loop.srs\""),
+        ("rules/back.srs", "include \"../rules/back.srs\""),
+    ]);
+    let resolve = |path: &str| files.get(path).map(|x| x.to_string());
+    assert!(
+        Ruleset::parse_with_includes("include \"peg.srs\"", resolve).unwrap() == build_1dpeg_rs(),
+        "nested includes failed to recreate 1dpeg"
+    );
+    //Includes are relative to the file they're in, and the same file written differently is still a cycle
+    assert!(
+        Ruleset::parse_with_includes("include \"rules/peg.srs\"", resolve).unwrap()
+            == build_1dpeg_rs(),
+        "includes in a subdirectory failed to recreate 1dpeg"
+    );
+    for (path, cycle) in [
+        ("rules/loop.srs", "./loop.srs"),
+        ("rules/back.srs", "../rules/back.srs"),
+    ] {
+        let errors =
+            Ruleset::parse_with_includes(&format!("include \"{}\"", path), resolve).unwrap_err();
+        assert!(
+            matches!(&errors[0].kind, RuleParseErrorKind::Include { errors, .. }
+            if errors[0].kind == RuleParseErrorKind::IncludeCycle(cycle.to_owned()))
+        );
+    }
+
+    //From disk, including the top-level file again is a cycle too
+    let dir = std::env::temp_dir().join(format!("srs_to_dfa_includes_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("top.srs"), "include \"sub/a.srs\"").unwrap();
+    std::fs::write(dir.join("sub/a.srs"), "1 1 0 - 0 0 1\ninclude \"b.srs\"").unwrap();
+    std::fs::write(dir.join("sub/b.srs"), "0 1 1 - 1 0 0").unwrap();
+    let parsed = Ruleset::parse_file(&dir.join("top.srs")).unwrap();
+    assert!(parsed.unwrap() == build_1dpeg_rs());
+    std::fs::write(
+        dir.join("sub/b.srs"),
+        "0 1 1 - 1 0 0\ninclude \"../top.srs\"",
+    )
+    .unwrap();
+    let errors = Ruleset::parse_file(&dir.join("top.srs"))
+        .unwrap()
+        .unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let mut kind = &errors[0].kind;
+    while let RuleParseErrorKind::Include { errors, .. } = kind {
+        kind = &errors[0].kind;
+    }
+    assert_eq!(
+        *kind,
+        RuleParseErrorKind::IncludeCycle("../top.srs".to_owned())
+    );
+
+    let errors = Ruleset::parse_with_includes(
+        "x 1 - 1 x where x in pegs\nx 1 - 1 0 where x in {0}, y in {1}\ninclude \"loop.srs\"\ninclude \"gone.srs\"",
+        resolve,
+    )
+    .unwrap_err();
+    let kinds: Vec<_> = errors.into_iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds[0],
+        RuleParseErrorKind::UndefinedClass("pegs".to_owned())
+    );
+    assert_eq!(kinds[1], RuleParseErrorKind::UnusedVariable("y".to_owned()));
+    assert!(
+        matches!(&kinds[2], RuleParseErrorKind::Include { path, errors }
+        if path == "loop.srs" && errors[0].kind == RuleParseErrorKind::IncludeCycle("loop.srs".to_owned()))
+    );
+    assert_eq!(
+        kinds[3],
+        RuleParseErrorKind::IncludeNotFound("gone.srs".to_owned())
+    );
+
+    assert_eq!(
+        Ruleset::parse("include \"peg.srs\"").unwrap_err()[0].kind,
+        RuleParseErrorKind::IncludeNotFound("peg.srs".to_owned())
+    );
+}
+
+#[test]
+fn track_lifting() {
+    //Vertical moves in 1 row are impossible, so lifting to 1 row should change nothing
+    let lifted = lift_ruleset(&build_1dpeg_rs(), 1, &[Move::Horizontal, Move::Vertical]).unwrap();
+    assert!(lifted == build_1dpeg_rs(), "1 row lift changed 1dpeg");
+
+    //2 rows: horizontal 110 -> 001 on either row with the other row free
+    let lifted = lift_ruleset(&build_1dpeg_rs(), 2, &[Move::Horizontal]).unwrap();
+    assert_eq!(
+        lifted.symbol_set.representations,
+        vec!["00", "01", "10", "11"]
+    );
+    assert_eq!(
+        lifted.rules.values().map(|x| x.len()).sum::<usize>(),
+        2 * 2 * 8
+    );
+    assert!(lifted.rules[&vec![3, 1, 0]].contains(&vec![2, 0, 1]));
+    assert!(lifted.rules.keys().all(|lhs| lhs.len() == 3));
+
+    //Vertical moves act on a single column, first symbol of the rule in the top row
+    let lifted = lift_ruleset(&build_1dpeg_rs(), 3, &[Move::Vertical]).unwrap();
+    assert_eq!(lifted.rules.len(), 2);
+    assert_eq!(lifted.rules[&vec![6]], vec![vec![1]]);
+
+    let goal = lift_goal(&build_onlyone1(), 3, 0).unwrap();
+    let symset = &goal.symbol_set;
+    let board = |cols: &[&str]| symset.string_to_symbols(&cols.to_vec()).unwrap();
+    assert!(goal.contains(&board(&["000", "010", "000"])));
+    assert!(!goal.contains(&board(&["000", "010", "010"])));
+    assert!(!goal.contains(&board(&["001", "010", "000"])));
+    assert!(!goal.contains(&board(&["000", "000"])));
+
+    let goal = lift_goal_each_row(&build_onlyone1(), 2).unwrap();
+    let symset = &goal.symbol_set;
+    let board = |cols: &[&str]| symset.string_to_symbols(&cols.to_vec()).unwrap();
+    assert!(goal.contains(&board(&["01", "00", "10"])));
+    assert!(goal.contains(&board(&["11"])));
+    assert!(!goal.contains(&board(&["01", "01"])));
+
+    let solve_test = MinkidSolver::new(
+        lift_ruleset(&build_1dpeg_rs(), 2, &[Move::Horizontal, Move::Vertical]).unwrap(),
+        lift_goal(&build_onlyone1(), 2, 0).unwrap(),
+    )
+    .unwrap();
+    let result = solve_test.run(4, vec![]);
+    let symset = &result.symbol_set;
+    let board = |cols: &[&str]| symset.string_to_symbols(&cols.to_vec()).unwrap();
+    assert!(result.contains(&board(&["01", "01", "00"])));
+    assert!(result.contains(&board(&["00", "01", "01"])));
+    assert!(!result.contains(&board(&["01", "00", "01"])));
+}
+
+#[test]
+fn anchored_rules() {
+    let anchored_srs = "1 1 0 - 0 0 1\n0 1 1 - 1 0 0\n^ 1 1 - 0 1\n1 1 $ - 1 0";
+    let rules = Ruleset::parse(anchored_srs).unwrap();
+    assert_eq!(rules.rules.len(), 2);
+    assert!(rules
+        .anchored_rules
+        .contains(&(Anchor::Start, vec![1, 1], vec![0, 1])));
+    assert!(rules
+        .anchored_rules
+        .contains(&(Anchor::End, vec![1, 1], vec![1, 0])));
+    assert!(
+        rules == Ruleset::parse(&rules.to_string()).unwrap(),
+        "anchored ruleset failed to recreate itself"
+    );
+
+    let kinds: Vec<_> = Ruleset::parse("1 ^ 1 - 0 1\n1 1 - $ 0 1\n^ 1 1 - 0 1\n^ 1 1 - 0 1")
+        .unwrap_err()
+        .into_iter()
+        .map(|e| e.kind)
+        .collect();
+    assert_eq!(kinds[0], RuleParseErrorKind::MisplacedAnchor);
+    assert_eq!(kinds[1], RuleParseErrorKind::MisplacedAnchor);
+    assert_eq!(
+        kinds[2],
+        RuleParseErrorKind::DuplicateRule { first_line: 3 }
+    );
+
+    let solve_test = MinkidSolver::new(rules.clone(), build_onlyone1()).unwrap();
+    //Anchored rules only fire at their end of the string
+    let next = solve_test.single_rule_hash(&vec![1, 1, 1, 1]);
+    assert!(next.contains(&vec![0, 1, 1, 1]));
+    assert!(next.contains(&vec![1, 1, 1, 0]));
+    assert!(!next.contains(&vec![1, 0, 1, 1]));
+
+    //Every board up to length 7 should agree with a brute force search
+    let solvable = |board: &Vec<SymbolIdx>| {
+        let mut seen = std::collections::HashSet::from([board.clone()]);
+        let mut queue = vec![board.clone()];
+        while let Some(next) = queue.pop() {
+            if solve_test.get_goal().contains(&next) {
+                return true;
+            }
+            for option in solve_test.single_rule_hash(&next) {
+                if seen.insert(option.clone()) {
+                    queue.push(option);
+                }
+            }
+        }
+        false
+    };
+    let minkid_dfa = solve_test.run(5, vec![]);
+    for len in 0..=7 {
+        for bits in 0..(1 << len) {
+            let board: Vec<SymbolIdx> = (0..len).map(|i| (bits >> i) & 1).collect();
+            assert_eq!(
+                minkid_dfa.contains(&board),
+                solvable(&board),
+                "minkid disagrees on {:?}",
+                board
+            );
+        }
+    }
+}
+
+#[test]
+fn anchored_rules_brute_force() {
+    let binary = build_onlyone1().symbol_set;
+    for srs in [
+        "1 1 0 - 0 0 1\n0 1 1 - 1 0 0\n^ 1 1 - 0 1",
+        "1 1 0 - 0 0 1\n0 1 1 - 1 0 0\n1 1 $ - 1 0",
+        "1 1 0 - 0 0 1\n^ 1 1 - 0 1\n1 1 $ - 1 0\n^ 1 0 1 $ - 0 1 0",
+    ] {
+        let rules = Ruleset::parse(srs).unwrap();
+        let goal = build_onlyone1();
+        let boards: Vec<Vec<SymbolIdx>> = binary.sig_set_iter(7).collect();
+        let solvable: Vec<bool> = boards
+            .iter()
+            .map(|board| brute_force_solvable(&rules, &goal, board))
+            .collect();
+        let minkid = MinkidSolver::new(rules.clone(), goal.clone()).unwrap();
+        let subset = SubsetSolver::new(rules.clone(), goal.clone()).unwrap();
+        let result = minkid.run(5, vec![]);
+        let subset_result = subset.run(5, vec![]);
+        for (board, solvable) in boards.iter().zip(&solvable) {
+            assert_eq!(
+                result.contains(board),
+                *solvable,
+                "minkid disagrees on {:?} with {:?}",
+                board,
+                srs
+            );
+            assert_eq!(
+                subset_result.contains(board),
+                *solvable,
+                "subset disagrees on {:?} with {:?}",
+                board,
+                srs
+            );
+        }
+
+        //The rule graph has an edge for exactly the pairs of states some single rewrite goes between.
+        //Some states are only reached by longer boards, so this looks further than the other checks
+        let graph = minkid.build_rule_graph(&result);
+        let edges: HashSet<(usize, usize)> = graph
+            .raw_edges()
+            .iter()
+            .map(|edge| (graph[edge.source()], graph[edge.target()]))
+            .collect();
+        let rewrites: HashSet<(usize, usize)> = binary
+            .sig_set_iter(10)
+            .flat_map(|board| {
+                rules
+                    .rewrites(&board)
+                    .into_iter()
+                    .map(|next| (result.final_state(&board), result.final_state(&next)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert!(edges == rewrites, "rule graph is wrong for {:?}", srs);
+
+        //Terminal strings are the ones no rule applies to, anchored or not
+        let no_rule_dfa = minkid.build_no_rule_dfa();
+        for board in &boards {
+            assert_eq!(
+                no_rule_dfa.contains(board),
+                rules.rewrites(board).is_empty(),
+                "{:?} is misjudged as terminal with {:?}",
+                board,
+                srs
+            );
+        }
+
+        //is_correct can't prove these, but must never accept a DFA that disagrees with brute force
+        for candidate in [
+            result.clone(),
+            goal.clone(),
+            &result ^ &DFA::from_regex("1 1", &binary).unwrap(),
+            &result ^ &DFA::from_regex("1 0 1", &binary).unwrap(),
+            &result ^ &DFA::from_regex("0 1 1 0 1", &binary).unwrap(),
+        ] {
+            let agrees = boards
+                .iter()
+                .zip(&solvable)
+                .all(|(board, solvable)| candidate.contains(board) == *solvable);
+            assert!(
+                agrees || !minkid.is_correct(&candidate),
+                "minkid is_correct is wrong for {:?}",
+                srs
+            );
+            assert!(
+                agrees || !subset.is_correct(&candidate),
+                "subset is_correct is wrong for {:?}",
+                srs
+            );
+        }
+    }
+}
+
+#[test]
+fn termination() {
+    for rules in [
+        build_1dpeg_rs(),
+        build_threerulesolver_rs(),
+        build_default2dpegx3_rs(),
+    ] {
+        match check_termination(&rules) {
+            Termination::Terminating(certificate) => assert!(verify(&rules, &certificate)),
+            other => panic!("failed to prove termination, got {:?}", other),
+        }
+    }
+    let rules = Ruleset::parse("1 0 - 0 1").unwrap();
+    assert_eq!(
+        check_termination(&rules),
+        Termination::Terminating(Certificate::LengthLex(vec![0, 1]))
+    );
+    //Generating, so only a match-bound can show this one terminates
+    let rules = Ruleset::parse("a a - a b a").unwrap();
+    assert!(matches!(
+        check_termination(&rules),
+        Termination::Terminating(Certificate::MatchBound(_))
+    ));
+
+    //A 3-cycle with no two rules undoing each other
+    let rules = Ruleset::parse("0 1 1 - 1 1 0\n1 1 0 - 1 0 1\n1 0 1 - 0 1 1").unwrap();
+    let solve_test = MinkidSolver::new(rules.clone(), build_onlyone1()).unwrap();
+    match SubsetSolver::new(rules, build_onlyone1()) {
+        Err(DomainError::Cyclic(cycle)) => {
+            assert_eq!(cycle.len(), 3);
+            for (idx, string) in cycle.iter().enumerate() {
+                assert!(solve_test
+                    .single_rule_hash(string)
+                    .contains(&cycle[(idx + 1) % cycle.len()]));
+            }
+        }
+        _ => panic!("SubsetSolver accepted a cyclic ruleset"),
+    }
+    assert!(matches!(
+        check_termination(&build_flip_rs()),
+        Termination::Cycle(_)
+    ));
+
+    //Cycles on "a L x b", which no single LHS or overlapping pair of them leads to
+    let rules = Ruleset::parse("L x - x L\nL b - R b\nx R - R x\na R - a L").unwrap();
+    assert_eq!(check_termination(&rules), Termination::Unknown);
+    let goal = DFA::from_regex("a x L b", &rules.symbol_set).unwrap();
+    assert!(matches!(
+        SubsetSolver::new(rules, goal),
+        Err(DomainError::UnknownTermination)
+    ));
+}
+
+#[test]
+fn local_confluence() {
+    //Overlapping with itself on "1 1 1" gives "0 1" and "1 0", which can't be joined
+    let rules = Ruleset::parse("1 1 - 0").unwrap();
+    assert_eq!(
+        rules.critical_pairs(),
+        vec![CriticalPair {
+            overlap: vec![1, 1, 1],
+            anchor: None,
+            left: vec![0, 1],
+            right: vec![1, 0],
+        }]
+    );
+    let failures = rules.check_local_confluence(3);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(
+        failures[0].to_string(&rules.symbol_set),
+        "\"1 1 1\" can be rewritten to both \"0 1\" and \"1 0\""
+    );
+    //Multiplication in Z2 is associative, so any two ways of reducing a string meet again
+    let rules = Ruleset::parse("0 0 - 0\n0 1 - 1\n1 0 - 1\n1 1 - 0").unwrap();
+    assert!(!rules.critical_pairs().is_empty());
+    assert!(rules.check_local_confluence(3).is_empty());
+
+    //Anchored rules only overlap where both of them can apply
+    let rules = Ruleset::parse("^ 1 0 - 0 0\n0 1 - 1 1").unwrap();
+    assert_eq!(rules.critical_pairs().len(), 1);
+    assert_eq!(rules.critical_pairs()[0].anchor, Some(Anchor::Start));
+    let rules = Ruleset::parse("1 0 $ - 0 0\n0 1 - 1 1").unwrap();
+    assert_eq!(
+        rules.critical_pairs(),
+        vec![CriticalPair {
+            overlap: vec![0, 1, 0],
+            anchor: Some(Anchor::End),
+            left: vec![1, 1, 0],
+            right: vec![0, 0, 0],
+        }]
+    );
+
+    assert!(!build_1dpeg_rs().check_local_confluence(5).is_empty());
+}
+
+#[test]
+fn completion() {
+    let rules = Ruleset::parse("1 1 - 0").unwrap();
+    let zero = rules
+        .symbol_set
+        .representations
+        .iter()
+        .position(|x| x == "0")
+        .unwrap() as SymbolIdx;
+    let one = 1 - zero;
+    //"1 1 1" is the only problem, fixed by sorting all 0s to the front
+    let order = ReductionOrder::ShortLex(vec![zero, one]);
+    let completed = rules.complete(&order, CompletionBudget::default()).unwrap();
+    assert_eq!(completed.rules.len(), 2);
+    assert_eq!(completed.rules[&vec![one, one]], vec![vec![zero]]);
+    assert_eq!(completed.rules[&vec![one, zero]], vec![vec![zero, one]]);
+    assert!(completed.check_local_confluence(5).is_empty());
+    assert!(matches!(
+        check_termination(&completed),
+        Termination::Terminating(_)
+    ));
+
+    //Weights can orient rules the other way around from ShortLex
+    let rules = Ruleset::parse("1 - 0 0").unwrap();
+    let order = ReductionOrder::WeightLex(vec![1, 3], vec![0, 1]);
+    let completed = rules.complete(&order, CompletionBudget::default()).unwrap();
+    assert!(completed
+        .rule_pairs()
+        .all(|(lhs, rhs)| order.greater(lhs, rhs)));
+
+    //The braid relation has no finite completion under ShortLex
+    let rules = Ruleset::parse("a b a - b a b").unwrap();
+    let budget = CompletionBudget {
+        max_rules: 10,
+        max_rounds: 10,
+    };
+    let partial = rules
+        .complete(&ReductionOrder::ShortLex(vec![0, 1]), budget)
+        .unwrap_err();
+    assert!(partial.rules.len() > 1);
+}
+
+#[test]
+fn symmetries() {
+    let reversal = |sym_count: SymbolIdx| Symmetry {
+        permutation: (0..sym_count).collect(),
+        reversed: true,
+    };
+    assert_eq!(
+        Symmetry::find_all(&build_1dpeg_rs(), &build_onlyone1()),
+        vec![reversal(2)]
+    );
+    //1 and 2 can't be swapped, since "1 1 0" has no "2 2 0" counterpart
+    assert_eq!(
+        Symmetry::find_all(&build_threerulesolver_rs(), &build_onlyone2()),
+        vec![reversal(3)]
+    );
+    //A goal that isn't symmetric breaks it
+    let mut goal = build_onlyone1();
+    goal.state_transitions[0] = vec![2, 1];
+    assert_eq!(Symmetry::find_all(&build_1dpeg_rs(), &goal), vec![]);
+
+    //Two colours of peg that only jump over their own colour
+    let rules = Ruleset::from_vec(
+        vec![
+            (vec![1, 1, 0], vec![0, 0, 1]),
+            (vec![0, 1, 1], vec![1, 0, 0]),
+            (vec![2, 2, 0], vec![0, 0, 2]),
+            (vec![0, 2, 2], vec![2, 0, 0]),
+        ],
+        SymbolSet {
+            length: 3,
+            representations: vec!["0".to_owned(), "1".to_owned(), "2".to_owned()],
+        },
+    );
+    //Exactly one peg, of either colour
+    let mut goal = build_onlyone2();
+    goal.state_transitions[0] = vec![0, 1, 1];
+    let symmetries = Symmetry::find_all(&rules, &goal);
+    assert_eq!(symmetries.len(), 3);
+    assert!(symmetries.contains(&Symmetry {
+        permutation: vec![0, 2, 1],
+        reversed: true,
+    }));
+
+    let plain = MinkidSolver::new(rules.clone(), goal.clone()).unwrap();
+    let with_symmetries = plain.clone().with_symmetries(symmetries.clone());
+    assert!(plain.run(5, vec![]) == with_symmetries.run(5, vec![]));
+    let plain = SubsetSolver::new(rules.clone(), goal.clone()).unwrap();
+    let with_symmetries = plain.clone().with_symmetries(symmetries);
+    assert!(plain.run(5, vec![]) == with_symmetries.run(5, vec![]));
+
+    //Swapping the colours, against brute force
+    let with_symmetries = SubsetSolver::new(rules.clone(), goal.clone())
+        .unwrap()
+        .with_symmetries(Symmetry::find_all(&rules, &goal))
+        .run(5, vec![]);
+    for board in goal.symbol_set.sig_set_iter(6) {
+        assert_eq!(
+            with_symmetries.contains(&board),
+            brute_force_solvable(&rules, &goal, &board),
+            "subset with symmetries disagrees on {:?}",
+            board
+        );
+    }
+
+    //"1 0 - 0 1" only has its swapped image "0 1 - 1 0" as an anchored rule, so swapping isn't a symmetry
+    let rules = Ruleset::parse("1 0 - 0 1\n^ 0 1 - 1 0\n^ 1 0 - 0 1").unwrap();
+    let binary = build_onlyone1().symbol_set;
+    let goal = DFA::from_regex("0* | 1*", &binary).unwrap();
+    assert_eq!(Symmetry::find_all(&rules, &goal), vec![]);
+
+    //Only the goal tells these symbols apart, so it has to prune the search rather than trying all 11! orders
+    let symbols = SymbolSet::<String>::new((0..11).map(|x| format!("s{}", x)).collect());
+    let rules = Ruleset::new(
+        HashMap::from([(vec![0, 1], vec![vec![1, 0]])]),
+        symbols.clone(),
+    );
+    let goal = (2..11)
+        .map(|x| vec![format!("s{}", x); x].join(" "))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let goal = DFA::from_regex(&goal, &symbols).unwrap();
+    assert_eq!(
+        Symmetry::find_all(&rules, &goal),
+        vec![Symmetry {
+            permutation: vec![1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10],
+            reversed: true,
+        }]
+    );
+
+    let solve_test = SubsetSolver::new(build_1dpeg_rs(), build_onlyone1()).unwrap();
+    let symmetries = Symmetry::find_all(&solve_test.rules, &solve_test.goal);
+    assert!(
+        solve_test.run(5, vec![])
+            == solve_test
+                .clone()
+                .with_symmetries(symmetries)
+                .run(5, vec![])
+    );
+}
+
+#[test]
+fn project_files() {
+    let rules = Ruleset::parse("^ 1 1 0 - 0 0 1\n0 1 1 - 1 0 0\n1 0 1 $ - 0 1 0").unwrap();
+    let json = serde_json::to_string(&rules).unwrap();
+    assert_eq!(serde_json::from_str::<Ruleset>(&json).unwrap(), rules);
+
+    let mut project = Project::new(build_1dpeg_rs(), build_onlyone1(), 3, SolverType::Minkid);
+    let result = project.run().unwrap();
+    assert!(result.sig_k >= 3);
+    let solve_test = MinkidSolver::new(build_1dpeg_rs(), build_onlyone1()).unwrap();
+    assert!(result.dfa == solve_test.run(result.sig_k, vec![]));
+
+    let loaded = Project::from_json(&project.to_json()).unwrap();
+    assert_eq!(loaded.rules, project.rules);
+    assert!(loaded.goal == project.goal);
+    assert_eq!(loaded.solver, SolverType::Minkid);
+    let (loaded_result, result) = (loaded.result.unwrap(), project.result.unwrap());
+    assert_eq!(loaded_result.sig_k, result.sig_k);
+    assert_eq!(loaded_result.duration, result.duration);
+    assert!(loaded_result.dfa == result.dfa);
+
+    assert!(Project::from_json("{\"rules\": []}").is_err());
+}
+
+//Too slow to build a file big enough to overflow u16
+#[cfg(not(feature = "wide_symbols"))]
+#[test]
+fn symbol_limit() {
+    use srs_to_dfa::util::MAX_SYMBOLS;
+
+    let lines: Vec<String> = (0..MAX_SYMBOLS)
+        .map(|x| format!("s{} - s{}", x, x + 1))
+        .collect();
+    assert_eq!(
+        Ruleset::parse(&lines.join("\n")).unwrap_err(),
+        vec![RuleParseError {
+            line: MAX_SYMBOLS,
+            column: 1,
+            kind: RuleParseErrorKind::TooManySymbols(MAX_SYMBOLS + 1),
+        }]
+    );
+    let rules = Ruleset::parse(&lines[..MAX_SYMBOLS - 1].join("\n")).unwrap();
+    assert_eq!(rules.symbol_set.length, MAX_SYMBOLS);
+    //Every symbol has to be reachable when iterating over the symbol set
+    assert_eq!(rules.symbol_set.build_sig_k(1).len(), MAX_SYMBOLS + 1);
+
+    //9 rows of 2 symbols makes 512 columns
+    assert!(matches!(
+        lift_ruleset(&build_1dpeg_rs(), 9, &[Move::Vertical]),
+        Err(DomainError::TooManySymbols(512))
+    ));
+    assert!(matches!(
+        lift_goal(&build_onlyone1(), 9, 0),
+        Err(DomainError::TooManySymbols(512))
+    ));
+}
+
+#[cfg(feature = "wide_symbols")]
+#[test]
+fn wide_symbols() {
+    //9 rows of 2 symbols makes 512 columns
+    let lifted = lift_ruleset(&build_1dpeg_rs(), 9, &[Move::Vertical]).unwrap();
+    assert_eq!(lifted.symbol_set.length, 512);
+    let lines: Vec<String> = (0..300).map(|x| format!("s{} - s{}", x, x + 1)).collect();
+    let rules = Ruleset::parse(&lines.join("\n")).unwrap();
+    assert_eq!(rules.symbol_set.length, 301);
+}
+
+#[test]
+fn minimization() {
+    let solve_test = MinkidSolver::new(build_1dpeg_rs(), build_onlyone1()).unwrap();
+    let mut examples = vec![
+        build_all0(),
+        build_all000(),
+        build_onlyone1(),
+        build_onlyone2(),
+        build_1dpeg_result(),
+        build_2dpeg_goal(),
+        solve_test.run(5, vec![]),
+        lift_goal(&build_onlyone1(), 2, 0).unwrap(),
+        lift_goal_each_row(&build_onlyone1(), 2).unwrap(),
+    ];
+    //Pairs of equivalent states, plus an unreachable one
+    let mut doubled = build_1dpeg_result().dfa_product(&build_onlyone1(), |a, _| *a);
+    doubled
+        .state_transitions
+        .push(vec![0; doubled.symbol_set.length]);
+    doubled.accepting_states.push(false);
+    examples.push(doubled);
+    for dfa in examples {
+        let mut minimized = dfa.clone();
+        minimized.minimize();
+        assert!(minimized == dfa);
+        assert_eq!(minimized.state_transitions.len(), moore_class_count(&dfa));
+        //Minimizing again changes nothing
+        let mut again = minimized.clone();
+        again.minimize();
+        assert_eq!(again.state_transitions, minimized.state_transitions);
+        assert_eq!(again.starting_state, minimized.starting_state);
+    }
+}
+
+#[test]
+fn products() {
+    let peg = build_1dpeg_result();
+    let one = build_onlyone1();
+    let solved = MinkidSolver::new(build_1dpeg_rs(), build_onlyone1())
+        .unwrap()
+        .run(5, vec![]);
+    let majority = DFA::product_many(&[&peg, &one, &solved], |outputs| {
+        outputs.iter().filter(|x| ***x).count() >= 2
+    });
+    let majority_min = DFA::product_many_minimized(&[&peg, &one, &solved], |outputs| {
+        outputs.iter().filter(|x| ***x).count() >= 2
+    });
+    assert!(majority == majority_min);
+    let peg_min = DFA::product_many_minimized(&[&peg], |outputs| *outputs[0]);
+    assert!(peg_min == peg);
+    assert_eq!(
+        majority_min.state_transitions.len(),
+        moore_class_count(&majority)
+    );
+    for len in 0..8 {
+        for bits in 0..(1u32 << len) {
+            let string: Vec<SymbolIdx> = (0..len).map(|x| ((bits >> x) & 1) as SymbolIdx).collect();
+            let votes = [&peg, &one, &solved]
+                .iter()
+                .filter(|x| x.contains(&string))
+                .count();
+            assert_eq!(majority.contains(&string), votes >= 2);
+        }
+    }
+    //Binary products agree with the operators
+    assert!(DFA::product_many(&[&peg, &one], |x| *x[0] & *x[1]) == &peg & &one);
+    assert!(DFA::product_many(&[&peg, &one], |x| *x[0] | *x[1]) == &peg | &one);
+    assert!(DFA::product_many(&[&peg, &one], |x| *x[0] ^ *x[1]) == &peg ^ &one);
+    assert!(DFA::product_many(&[&peg], |x| *x[0]) == peg);
+}
+
+#[test]
+fn regex_goals() {
+    let binary = build_onlyone1().symbol_set;
+    let only_one = DFA::from_regex("0* 1 0*", &binary).unwrap();
+    assert!(only_one == build_onlyone1());
+    assert_eq!(only_one.state_transitions.len(), 3);
+    type Matcher = fn(&[SymbolIdx]) -> bool;
+    let cases: Vec<(&str, Matcher)> = vec![
+        ("(0|1)* 1 1", |x| x.ends_with(&[1, 1])),
+        ("!(.* 1 1 .*)", |x| !x.windows(2).any(|w| w == [1, 1])),
+        (".* 1 .* & .*0.*", |x| x.contains(&0) && x.contains(&1)),
+        ("[^0]+ 0?", |x| {
+            !x.is_empty() && x[..x.len() - 1].iter().all(|s| *s == 1) && x[0] == 1
+        }),
+        ("()", |x| x.is_empty()),
+        ("(0 0)* | [1]", |x| {
+            x == [1] || (x.len() % 2 == 0 && !x.contains(&1))
+        }),
+    ];
+    for (regex, matches) in cases {
+        let dfa = DFA::from_regex(regex, &binary).unwrap();
+        assert_eq!(dfa.state_transitions.len(), moore_class_count(&dfa));
+        for len in 0..8 {
+            for bits in 0..(1u32 << len) {
+                let string: Vec<SymbolIdx> =
+                    (0..len).map(|x| ((bits >> x) & 1) as SymbolIdx).collect();
+                assert_eq!(
+                    dfa.contains(&string),
+                    matches(&string),
+                    "{} on {:?}",
+                    regex,
+                    string
+                );
+            }
+        }
+    }
+    //Multi-character symbols
+    let pairs = SymbolSet::<String>::new(
+        ["00", "01", "10", "11"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect(),
+    );
+    let dfa = DFA::from_regex("00 (01 | 10)*", &pairs).unwrap();
+    assert!(dfa.contains(&vec![0, 1, 2, 1]));
+    assert!(!dfa.contains(&vec![0, 3]));
+    assert!(!dfa.contains(&vec![1]));
+    let error = |regex: &str| DFA::from_regex(regex, &binary).err().unwrap();
+    assert_eq!(
+        error("0 |"),
+        RegexParseError {
+            column: 4,
+            kind: RegexParseErrorKind::UnexpectedEnd
+        }
+    );
+    assert_eq!(
+        error("0 2"),
+        RegexParseError {
+            column: 3,
+            kind: RegexParseErrorKind::UnknownSymbol("2".to_owned())
+        }
+    );
+    assert_eq!(error(" (0 1").kind, RegexParseErrorKind::UnclosedParen);
+    assert_eq!(error(" (0 1").column, 2);
+    assert_eq!(error("[0 1").kind, RegexParseErrorKind::UnclosedClass);
+    assert_eq!(
+        error("0 )"),
+        RegexParseError {
+            column: 3,
+            kind: RegexParseErrorKind::UnexpectedToken(")".to_owned())
+        }
+    );
+    assert_eq!(
+        error("*0").kind,
+        RegexParseErrorKind::UnexpectedToken("*".to_owned())
+    );
+}
+
+#[test]
+fn regex_output() {
+    assert_eq!(build_onlyone1().to_regex(), "0* 1 0*");
+    assert_eq!(build_all0().to_regex(), "0*");
+    let binary = build_onlyone1().symbol_set;
+    let mut examples = vec![
+        build_onlyone2(),
+        build_1dpeg_result(),
+        build_2dpeg_goal(),
+        MinkidSolver::new(build_1dpeg_rs(), build_onlyone1())
+            .unwrap()
+            .run(5, vec![]),
+        lift_goal_each_row(&build_onlyone1(), 2).unwrap(),
+    ];
+    for regex in ["!(.* 1 1 .*)", "(0 1 | 1 0)* 1?", "!.*", "()", ".* 0 . ."] {
+        examples.push(DFA::from_regex(regex, &binary).unwrap());
+    }
+    for dfa in examples {
+        let regex = dfa.to_regex();
+        assert!(
+            DFA::from_regex(&regex, &dfa.symbol_set).unwrap() == dfa,
+            "{}",
+            regex
+        );
+    }
+    assert_eq!(build_1dpeg_result().to_regex_within(3), None);
+}
+
+#[test]
+fn dot_export() {
+    let dfa = build_onlyone1();
+    assert_eq!(
+        dfa.to_dot(&DotOptions::default()),
+        "digraph DFA {
+    rankdir=LR;
+    node [shape=circle];
+    start [shape=point];
+    start -> q0;
+    q0 [label=\"q0\"];
+    q1 [label=\"q1\", shape=doublecircle];
+    q2 [label=\"q2\"];
+    q0 -> q0 [label=\"0\"];
+    q0 -> q1 [label=\"1\"];
+    q1 -> q1 [label=\"0\"];
+    q1 -> q2 [label=\"1\"];
+    q2 -> q2 [label=\"0, 1\"];
+}
+"
+    );
+    let options = DotOptions {
+        merge_edges: false,
+        hide_sink: true,
+        access_labels: true,
+    };
+    let mut with_unreachable = dfa.clone();
+    with_unreachable.state_transitions.push(vec![0, 0]);
+    with_unreachable.accepting_states.push(false);
+    let dot = with_unreachable.to_dot(&options);
+    assert!(!dot.contains("q2"));
+    assert!(dot.contains("q0 [label=\"q0\\nε\"];"));
+    assert!(dot.contains("q1 [label=\"q1\\n1\", shape=doublecircle];"));
+    assert!(dot.contains("q3 [label=\"q3\\nunreachable\"];"));
+    assert!(dot.contains("q3 -> q0 [label=\"0\"];\n    q3 -> q0 [label=\"1\"];"));
+}
+
+#[test]
+fn nfas() {
+    let binary = build_onlyone1().symbol_set;
+    //Second to last symbol is a 1
+    let mut nfa = NFA::new(binary.clone());
+    for accepting in [false, false, true] {
+        nfa.add_state(accepting);
+    }
+    nfa.starting_states = vec![0];
+    nfa.add_transition(0, 0, 0);
+    nfa.add_transition(0, 1, 0);
+    nfa.add_transition(0, 1, 1);
+    nfa.add_transition(1, 0, 2);
+    nfa.add_transition(1, 1, 2);
+    assert!(!nfa.is_deterministic());
+    let mut dfa = nfa.to_dfa();
+    assert!(dfa == DFA::from_regex(".* 1 .", &binary).unwrap());
+    dfa.minimize();
+    assert_eq!(dfa.state_transitions.len(), 4);
+    assert!(nfa.reverse().to_dfa() == DFA::from_regex(". 1 .*", &binary).unwrap());
+    //Makes the last symbol optional
+    nfa.add_epsilon_transition(1, 2);
+    assert_eq!(nfa.epsilon_closure([1]), BTreeSet::from([1, 2]));
+    assert!(nfa.to_dfa() == DFA::from_regex(".* 1 .?", &binary).unwrap());
+    assert!(NFA::from(&build_onlyone1()).is_deterministic());
+    assert!(NFA::from(&build_1dpeg_result()).to_dfa() == build_1dpeg_result());
+
+    //JFLAP files holding NFAs are determinised instead of having transitions overwritten
+    let jflap = r#"<?xml version="1.0" encoding="UTF-8"?>
+<structure>
+    <type>fa</type>
+    <automaton>
+        <state id="5" name="q0"><initial/></state>
+        <state id="6" name="q1"></state>
+        <state id="7" name="q2"><final/></state>
+        <transition><from>5</from><to>5</to><read>0</read></transition>
+        <transition><from>5</from><to>5</to><read>1</read></transition>
+        <transition><from>5</from><to>6</to><read>1</read></transition>
+        <transition><from>6</from><to>7</to><read>0</read></transition>
+        <transition><from>6</from><to>7</to><read>1</read></transition>
+        <transition><from>6</from><to>7</to><read/></transition>
+    </automaton>
+</structure>"#;
+    assert!(
+        DFA::load_jflap_from_string(jflap).unwrap() == DFA::from_regex(".* 1 .?", &binary).unwrap()
+    );
+    //Deterministic files keep their states as-is
+    let saved = String::from_utf8(build_1dpeg_result().save_jflap_to_bytes()).unwrap();
+    let loaded = DFA::load_jflap_from_string(&saved).unwrap();
+    assert_eq!(
+        loaded.state_transitions,
+        build_1dpeg_result().state_transitions
+    );
+    assert_eq!(
+        loaded.accepting_states,
+        build_1dpeg_result().accepting_states
+    );
+}
+
+#[test]
+fn jflap_import() {
+    let only_one = |body: &str| {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><structure><type>fa</type>{}</structure>",
+            body
+        )
+    };
+    let transitions = "<transition><from>a</from><to>a</to><read>0</read></transition>
+        <transition><from>a</from><to>b</to><read>1</read></transition>
+        <transition><from>b</from><to>b</to><read>0</read></transition>";
+    //States after transitions, non-numeric ids, no <automaton> wrapper (as JFLAP 6 writes it), and a missing sink
+    let jflap6 = only_one(&format!(
+        "{}<state id=\"a\"><initial/></state><state id=\"b\"><final/></state>",
+        transitions
+    ));
+    assert!(DFA::load_jflap_from_string(&jflap6).unwrap() == build_onlyone1());
+    //Building blocks are read as states, and the machines inside them are ignored
+    let blocks = only_one(&format!(
+        "<automaton><block id=\"a\" name=\"q0\"><tag>Machine0</tag><initial/></block>
+        <block id=\"b\" name=\"q1\"><tag>Machine1</tag><final/></block>{}
+        <Machine0><structure><type>fa</type><state id=\"c\"><final/></state></structure></Machine0>
+        </automaton>",
+        transitions
+    ));
+    assert!(DFA::load_jflap_from_string(&blocks).unwrap() == build_onlyone1());
+
+    let error = |body: &str| DFA::load_jflap_from_string(&only_one(body)).err().unwrap();
+    assert!(matches!(
+        DFA::load_jflap_from_string("<structure><type>fa</type>"),
+        Err(JflapError::Xml(_))
+    ));
+    assert_eq!(
+        DFA::load_jflap_from_string("<structure><type>turing</type></structure>").err(),
+        Some(JflapError::UnsupportedType("turing".to_owned()))
+    );
+    assert_eq!(
+        error("<state name=\"q0\"><initial/></state>"),
+        JflapError::MissingId(Some("q0".to_owned()))
+    );
+    assert_eq!(
+        error("<state id=\"0\"><initial/></state><state id=\"0\"/>"),
+        JflapError::DuplicateState("0".to_owned())
+    );
+    assert_eq!(
+        error(&format!(
+            "<state id=\"a\"><initial/></state>{}",
+            transitions
+        )),
+        JflapError::UnknownState("b".to_owned())
+    );
+    assert_eq!(
+        error(
+            "<state id=\"0\"><initial/></state><transition><to>0</to><read>0</read></transition>"
+        ),
+        JflapError::MalformedTransition
+    );
+    assert_eq!(
+        error("<state id=\"0\"><final/></state>"),
+        JflapError::NoInitialState
+    );
+}
+
+#[test]
+fn jflap_layout() {
+    let mut dfa = build_onlyone1();
+    dfa.state_transitions.push(vec![0, 1]);
+    dfa.accepting_states.push(false);
+    //Start, then the accepting state next to it, the sink below and to the right, and the unreachable state at the bottom
+    assert_eq!(
+        dfa.jflap_layout(),
+        vec![(60.0, 60.0), (180.0, 60.0), (300.0, 180.0), (60.0, 300.0)]
+    );
+    let peg = build_1dpeg_result();
+    let layout = peg.jflap_layout();
+    let distinct: HashSet<String> = layout.iter().map(|x| format!("{:?}", x)).collect();
+    assert_eq!(distinct.len(), peg.state_transitions.len());
+    let saved = String::from_utf8(peg.save_jflap_to_bytes()).unwrap();
+    assert!(saved.contains("<x>60.0</x>"));
+    assert_eq!(saved.matches("<y>").count(), peg.state_transitions.len());
+}
+
+#[test]
+fn language_operations() {
+    let binary = build_onlyone1().symbol_set;
+    let regex = |x: &str| DFA::from_regex(x, &binary).unwrap();
+    let only_one = regex("0* 1 0*");
+    let cases = vec![
+        (only_one.concat(&only_one), regex("0* 1 0* 1 0*")),
+        (regex("0 1").concat(&regex("1*")), regex("0 1+")),
+        (regex("0 1").star(), regex("(0 1)*")),
+        (regex("!()").star(), regex(".*")),
+        (regex("0 0 1").reverse(), regex("1 0 0")),
+        (regex(".* 1 .").reverse(), regex(". 1 .*")),
+        (only_one.left_quotient(&regex("1")), regex("0*")),
+        (only_one.left_quotient(&regex(".*")), regex("0* (1 0*)?")),
+        (
+            regex("0 1 1 | 1 0").left_quotient(&regex("0 | 1")),
+            regex("1 1 | 0"),
+        ),
+        (only_one.right_quotient(&regex("0* 1")), regex("0*")),
+        (
+            regex("0 1 1 | 1 0").right_quotient(&regex("1")),
+            regex("0 1"),
+        ),
+        (
+            regex("0 1 1").prefix_closure(),
+            regex("() | 0 | 0 1 | 0 1 1"),
+        ),
+        (only_one.prefix_closure(), regex("0* (1 0*)?")),
+        (
+            regex("0 1 1").suffix_closure(),
+            regex("() | 1 | 1 1 | 0 1 1"),
+        ),
+        ((!&only_one).suffix_closure(), regex(".*")),
+        (only_one.left_quotient(&regex("!.*")), regex("!.*")),
+    ];
+    for (i, (result, expected)) in cases.into_iter().enumerate() {
+        assert!(result == expected, "case {}", i);
+        assert_eq!(result.state_transitions.len(), moore_class_count(&result));
+    }
+}
+
+#[test]
+#[should_panic(expected = "DFAs must share a symbol set")]
+fn language_operations_symbol_mismatch() {
+    //Same number of symbols, but different ones
+    let letters = SymbolSet::<String>::new(vec!["a".to_owned(), "b".to_owned()]);
+    build_onlyone1().concat(&DFA::from_regex("a b", &letters).unwrap());
+}
+
+#[test]
+fn counting() {
+    let binary = build_onlyone1().symbol_set;
+    let regex = |x: &str| DFA::from_regex(x, &binary).unwrap();
+    //Against brute force
+    for dfa in [
+        build_onlyone1(),
+        regex("!(.* 1 1 .*)"),
+        regex("(0 0)* | 1"),
+        !&build_onlyone1(),
+    ] {
+        let table = dfa.count_accepted_table(10);
+        assert_eq!(table.len(), 11);
+        for (len, count) in table.iter().enumerate() {
+            let brute = (0..(1u32 << len))
+                .filter(|bits| {
+                    dfa.contains(&(0..len).map(|x| ((bits >> x) & 1) as SymbolIdx).collect())
+                })
+                .count();
+            assert_eq!(*count, BigUint::from(brute));
+            assert_eq!(dfa.count_accepted(len), *count);
+        }
+    }
+    assert_eq!(regex(".*").count_accepted(200), BigUint::from(1u8) << 200);
+    assert_eq!(
+        build_onlyone1().accepted_ratio(4),
+        BigRational::new(BigInt::from(1), BigInt::from(4))
+    );
+    assert_eq!(
+        regex("0 .*").accepted_ratio(300),
+        BigRational::new(BigInt::from(1), BigInt::from(2))
+    );
+
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    assert!(close(regex(".*").growth_rate(), 2.0));
+    assert!(close(
+        regex("!(.* 1 1 .*)").growth_rate(),
+        (1.0 + 5f64.sqrt()) / 2.0
+    ));
+    assert!(close(build_onlyone1().growth_rate(), 1.0));
+    //Periodic, and only the part that can still accept counts
+    assert!(close(regex("(0 0)* | 1 .*").growth_rate(), 2.0));
+    assert!(close(
+        regex("(0 0)* | 1 (0 1 | 1 0)*").growth_rate(),
+        2f64.sqrt()
+    ));
+    assert!(close(regex("0 1 | 1 1 0").growth_rate(), 0.0));
+    assert!(close(regex("!.*").growth_rate(), 0.0));
+}
+
+#[test]
+fn string_generation() {
+    let binary = build_onlyone1().symbol_set;
+    let regex = |x: &str| DFA::from_regex(x, &binary).unwrap();
+    //Shortlex order against brute force
+    for dfa in [build_onlyone1(), regex("!(.* 1 1 .*)"), regex("(0 0)* | 1")] {
+        let mut expected = vec![];
+        let mut rejected = vec![];
+        for len in 0..7 {
+            for bits in 0..(1u32 << len) {
+                //Most significant symbol first, so counting up is symbol order
+                let string: Vec<SymbolIdx> = (0..len)
+                    .rev()
+                    .map(|x| ((bits >> x) & 1) as SymbolIdx)
+                    .collect();
+                if dfa.contains(&string) {
+                    expected.push(string);
+                } else {
+                    rejected.push(string);
+                }
+            }
+        }
+        let found: Vec<_> = dfa.accepted_strings().take_while(|x| x.len() < 7).collect();
+        assert_eq!(found, expected);
+        let found: Vec<_> = dfa.rejected_strings().take_while(|x| x.len() < 7).collect();
+        assert_eq!(found, rejected);
+    }
+    //Finite languages run out
+    assert_eq!(
+        regex("0 1 | 1 1 0 | ()")
+            .accepted_strings()
+            .collect::<Vec<_>>(),
+        vec![vec![], vec![0, 1], vec![1, 1, 0]]
+    );
+    assert_eq!(regex("!.*").accepted_strings().next(), None);
+    assert_eq!(regex(".*").rejected_strings().next(), None);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let no_pairs = regex("!(.* 1 1 .*)");
+    for _ in 0..20 {
+        let string = no_pairs.sample_accepted(40, &mut rng).unwrap();
+        assert_eq!(string.len(), 40);
+        assert!(no_pairs.contains(&string));
+        let string = no_pairs.sample_rejected(40, &mut rng).unwrap();
+        assert!(!no_pairs.contains(&string));
+    }
+    assert_eq!(regex("(0 0)*").sample_accepted(3, &mut rng), None);
+    assert_eq!(regex(".*").sample_rejected(5, &mut rng), None);
+    assert_eq!(build_onlyone1().sample_accepted(0, &mut rng), None);
+    //Every string of length 4 with only one 1 about equally often
+    let mut seen = HashMap::new();
+    for _ in 0..4000 {
+        *seen
+            .entry(build_onlyone1().sample_accepted(4, &mut rng).unwrap())
+            .or_insert(0) += 1;
+    }
+    assert_eq!(seen.len(), 4);
+    assert!(seen.values().all(|x| (800..1200).contains(x)));
+}
+
+#[test]
+fn dfa_differences() {
+    let binary = build_onlyone1().symbol_set;
+    let regex = |x: &str| DFA::from_regex(x, &binary).unwrap();
+    let only_one = build_onlyone1();
+    assert_eq!(only_one.distinguishing_string(&regex("0* 1 0*")), None);
+    assert_eq!(
+        only_one.distinguishing_string(&regex("0* 1 0* (1 0*)?")),
+        Some(vec![1, 1])
+    );
+    assert_eq!(
+        regex(".* 1").distinguishing_string(&regex(".*")),
+        Some(vec![])
+    );
+    //Same symbols in another order
+    let flipped = SymbolSet {
+        length: 2,
+        representations: vec!["1".to_owned(), "0".to_owned()],
+    };
+    let flipped_one = DFA::from_regex("0* 1 0*", &flipped).unwrap();
+    assert!(only_one != flipped_one);
+    assert_eq!(only_one.distinguishing_string(&flipped_one), None);
+    assert_eq!(
+        only_one.distinguishing_string(&DFA::from_regex("1* 0 1*", &flipped).unwrap()),
+        Some(vec![0])
+    );
+
+    let diff = only_one.diff(&regex(".* 1 .*"), 3);
+    assert!(diff.only_first.is_empty());
+    assert_eq!(
+        diff.only_second,
+        vec![vec![1, 1], vec![0, 1, 1], vec![1, 0, 1]]
+    );
+    assert!(diff
+        .to_string()
+        .contains("Accepted only by the second:\n    \"1 1\"\n"));
+    let diff = regex("0").diff(&regex("1 | ()"), 10);
+    assert_eq!(diff.only_first, vec![vec![0]]);
+    assert_eq!(diff.only_second, vec![vec![], vec![1]]);
+    assert!(diff.to_string().contains("(empty string)"));
+    let diff = only_one.diff(&flipped_one, 10);
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "Both DFAs accept the same strings\n");
+}
+
+#[test]
+#[should_panic(expected = "DFAs must share a symbol set")]
+fn dfa_differences_symbol_mismatch() {
+    //Same number of symbols, but different ones
+    let letters = SymbolSet::<String>::new(vec!["a".to_owned(), "b".to_owned()]);
+    build_onlyone1().diff(&DFA::from_regex("a b", &letters).unwrap(), 10);
+}
+
+#[test]
+fn moore_results() {
+    //Number of pegs, counting 3 or more as 3. Fewer is better, hence Reverse
+    let pegs = DFA {
+        starting_state: 0,
+        state_transitions: vec![vec![0, 1], vec![1, 2], vec![2, 3], vec![3, 3]],
+        accepting_states: (0..4).map(Reverse).collect::<Vec<_>>(),
+        symbol_set: build_onlyone1().symbol_set,
+    };
+    assert_eq!(
+        pegs.outputs(),
+        vec![Reverse(3), Reverse(2), Reverse(1), Reverse(0)]
+    );
+    assert!(pegs.at_least(&Reverse(1)) == DFA::from_regex("0* (1 0*)?", &pegs.symbol_set).unwrap());
+    let levels: Vec<_> = pegs.outputs()[1..]
+        .iter()
+        .map(|x| (*x, pegs.at_least(x)))
+        .collect();
+    assert!(DFA::from_levels(Reverse(3), &levels) == pegs);
+
+    let solver = MooreSolver::<MinkidSolver, _>::new(build_1dpeg_rs(), pegs.clone()).unwrap();
+    assert_eq!(solver.get_levels().len(), 3);
+    let result = solver.run(5);
+    //Fewest pegs reachable, by brute force
+    let rules = build_default1dpeg::<MinkidSolver>().unwrap();
+    for len in 0..9 {
+        for bits in 0..(1u32 << len) {
+            let board: Vec<SymbolIdx> = (0..len).map(|x| ((bits >> x) & 1) as SymbolIdx).collect();
+            let mut seen = HashSet::from([board.clone()]);
+            let mut stack = vec![board.clone()];
+            while let Some(current) = stack.pop() {
+                for next in rules.single_rule_hash(&current) {
+                    if seen.insert(next.clone()) {
+                        stack.push(next);
+                    }
+                }
+            }
+            let best = seen.iter().map(|x| pegs.contains(x)).max().unwrap();
+            assert_eq!(result.contains(&board), best, "{:?}", board);
+        }
+    }
+    //Already minimal, and solvable to one peg exactly where the boolean solver says so
+    let mut minimized = result.clone();
+    minimized.minimize();
+    assert_eq!(
+        minimized.state_transitions.len(),
+        result.state_transitions.len()
+    );
+    let some_peg = DFA::from_regex(".* 1 .*", &pegs.symbol_set).unwrap();
+    assert!(&result.at_least(&Reverse(1)) & &some_peg == &build_1dpeg_result() & &some_peg);
+
+    let json = serde_json::to_string(&result).unwrap();
+    assert!(serde_json::from_str::<DFA<String, Reverse<usize>>>(&json).unwrap() == result);
+    //Outputs of any type can be read back for testing strings, without knowing what they are
+    let values = serde_json::from_str::<DFA<String, serde_json::Value>>(&json).unwrap();
+    assert_eq!(values.contains(&vec![1, 1, 0, 1]), serde_json::json!(1));
+
+    let constant =
+        MooreSolver::<MinkidSolver, _>::new(build_1dpeg_rs(), pegs.at_least(&Reverse(3)))
+            .unwrap()
+            .run(5);
+    assert_eq!(constant.state_transitions.len(), 1);
+    assert!(constant.accepting_states[0]);
+
+    let empty = DFA::<String, Reverse<u32>> {
+        starting_state: 0,
+        state_transitions: vec![],
+        accepting_states: vec![],
+        symbol_set: build_onlyone1().symbol_set,
+    };
+    assert!(matches!(
+        MooreSolver::<MinkidSolver, _>::new(build_1dpeg_rs(), empty),
+        Err(DomainError::EmptyGoal)
+    ));
+}
+
+#[test]
+fn binary_files() {
+    let binary = build_onlyone1().symbol_set;
+    let pairs = SymbolSet::<String>::new(
+        ["00", "01", "10", "é"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect(),
+    );
+    for dfa in [
+        build_1dpeg_result(),
+        build_onlyone2(),
+        DFA::from_regex("!(.* 1 1 .*)", &binary).unwrap(),
+        DFA::from_regex("(00 01 | é)* 10", &pairs).unwrap(),
+    ] {
+        let bytes = dfa.to_binary();
+        let loaded = DFA::from_binary(&bytes).unwrap();
+        assert_eq!(loaded.starting_state, dfa.starting_state);
+        assert_eq!(loaded.state_transitions, dfa.state_transitions);
+        assert_eq!(loaded.accepting_states, dfa.accepting_states);
+        assert_eq!(loaded.symbol_set, dfa.symbol_set);
+
+        let view = DFAView::new(&bytes[..]).unwrap();
+        assert_eq!(view.state_count(), dfa.state_transitions.len());
+        for string in dfa.symbol_set.sig_set_iter(6) {
+            assert_eq!(view.final_state(&string), dfa.final_state(&string));
+            assert_eq!(view.contains(&string), dfa.contains(&string));
+        }
+    }
+
+    let path = std::env::temp_dir().join(format!("srs_to_dfa_test_{}.dfab", std::process::id()));
+    build_1dpeg_result()
+        .save_binary(&mut File::create(&path).unwrap())
+        .unwrap();
+    let mapped = DFAView::open(&path).unwrap();
+    assert!(mapped.contains(&[1, 1, 0, 1]));
+    assert!(!mapped.contains(&[1, 0, 0, 1]));
+    assert!(mapped.to_dfa() == build_1dpeg_result());
+    drop(mapped);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(DFAView::open(&path), Err(BinaryDFAError::Io(_))));
+
+    //Broken files
+    let bytes = build_onlyone1().to_binary();
+    assert_eq!(
+        DFA::from_binary(b"{\"starting_state\": 0}").err().unwrap(),
+        BinaryDFAError::NotBinaryDFA
+    );
+    let mut newer = bytes.clone();
+    newer[4] = 2;
+    assert_eq!(
+        DFA::from_binary(&newer).err().unwrap(),
+        BinaryDFAError::UnsupportedVersion(2)
+    );
+    assert_eq!(
+        DFA::from_binary(&bytes[..bytes.len() - 1]).err().unwrap(),
+        BinaryDFAError::WrongLength
+    );
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert_eq!(
+        DFA::from_binary(&longer).err().unwrap(),
+        BinaryDFAError::WrongLength
+    );
+    //onlyone1 has under 8 states, so its accepting states take one byte after the last transition
+    let mut out_of_range = bytes.clone();
+    let last = out_of_range.len() - 5;
+    out_of_range[last] = 3;
+    assert_eq!(
+        DFA::from_binary(&out_of_range).err().unwrap(),
+        BinaryDFAError::StateOutOfRange(3)
+    );
+}
+
+#[test]
+#[should_panic(expected = "symbol 2 doesn't exist")]
+fn binary_view_symbol_out_of_range() {
+    //Past the end of a row is the next state's row, so it's refused rather than read
+    let bytes = build_onlyone1().to_binary();
+    DFAView::new(&bytes[..]).unwrap().transition(0, 2);
+}
+
+#[test]
+fn dfa_validation() {
+    let onlyone1 = build_onlyone1();
+    assert_eq!(onlyone1.validate(), Ok(()));
+    assert_eq!(build_1dpeg_result().validate(), Ok(()));
+    let json = serde_json::to_string(&onlyone1).unwrap();
+    assert!(serde_json::from_str::<DFA>(&json).unwrap() == onlyone1);
+
+    let broken = |change: fn(&mut DFA)| {
+        let mut dfa = build_onlyone1();
+        change(&mut dfa);
+        let loaded = serde_json::from_str::<DFA>(&serde_json::to_string(&dfa).unwrap());
+        assert_eq!(
+            loaded.err().unwrap().to_string(),
+            dfa.validate().unwrap_err().to_string()
+        );
+        dfa.validate().unwrap_err()
+    };
+    assert_eq!(
+        broken(|x| x.state_transitions[1][0] = 3),
+        DfaError::TransitionOutOfRange(1, 0)
+    );
+    assert_eq!(
+        broken(|x| x.state_transitions[2].truncate(1)),
+        DfaError::WrongRowLength(2)
+    );
+    assert_eq!(
+        broken(|x| x.accepting_states.push(true)),
+        DfaError::AcceptingStatesLength(4)
+    );
+    assert_eq!(
+        broken(|x| x.starting_state = 5),
+        DfaError::StartingStateOutOfRange(5)
+    );
+    assert_eq!(
+        broken(|x| x.symbol_set.length = 3),
+        DfaError::SymbolSetLength(3, 2)
+    );
+    assert_eq!(
+        broken(|x| {
+            x.state_transitions.clear();
+            x.accepting_states.clear();
+        }),
+        DfaError::NoStates
+    );
+
+    //Sinks and unreachable states
+    assert_eq!(onlyone1.sink_states(), vec![2]);
+    assert_eq!(build_onlyone2().sink_states(), vec![2]);
+    let mut padded = onlyone1.clone();
+    padded.state_transitions.insert(0, vec![1, 3]);
+    padded.accepting_states.insert(0, true);
+    for row in padded.state_transitions.iter_mut().skip(1) {
+        row.iter_mut().for_each(|x| *x += 1);
+    }
+    padded.starting_state = 1;
+    padded.state_transitions.push(vec![4, 4]);
+    padded.accepting_states.push(false);
+    assert_eq!(padded.sink_states(), vec![3, 4]);
+    padded.trim_unreachable();
+    assert_eq!(padded.validate(), Ok(()));
+    assert_eq!(padded.starting_state, 0);
+    assert_eq!(padded.state_transitions, onlyone1.state_transitions);
+    assert_eq!(padded.accepting_states, onlyone1.accepting_states);
+
+    //Completing partial DFAs
+    let mut complete = onlyone1.clone();
+    assert_eq!(complete.complete_with_sink(), None);
+    assert_eq!(complete.state_transitions, onlyone1.state_transitions);
+    let mut partial = onlyone1.clone();
+    partial.state_transitions = vec![vec![0, 1], vec![1, usize::MAX], vec![usize::MAX]];
+    assert_eq!(partial.complete_with_sink(), Some(2));
+    assert_eq!(partial.state_transitions, onlyone1.state_transitions);
+    let mut partial = onlyone1.clone();
+    partial.state_transitions = vec![vec![0, 1], vec![1]];
+    partial.accepting_states = vec![false, true];
+    assert_eq!(partial.complete_with_sink(), Some(2));
+    assert_eq!(partial.validate(), Ok(()));
+    assert!(partial == onlyone1);
+}
+
+//Slow reference for minimize: refines by (output, classes of successors) until nothing splits
+fn moore_class_count(dfa: &DFA) -> usize {
+    let mut classes: Vec<usize> = dfa.accepting_states.iter().map(|x| *x as usize).collect();
+    loop {
+        let mut ids = HashMap::new();
+        let next: Vec<usize> = (0..classes.len())
+            .map(|state| {
+                let successors: Vec<usize> = dfa.state_transitions[state]
+                    .iter()
+                    .map(|x| classes[*x])
+                    .collect();
+                let new_id = ids.len();
+                *ids.entry((classes[state], successors)).or_insert(new_id)
+            })
+            .collect();
+        if ids.len() == classes.iter().collect::<HashSet<_>>().len() {
+            return ids.len();
+        }
+        classes = next;
+    }
+}
+
+//Whether some sequence of rewrites takes the board into the goal. Only terminates for rulesets that don't grow strings
+fn brute_force_solvable(rules: &Ruleset, goal: &DFA, board: &[SymbolIdx]) -> bool {
+    let mut seen = HashSet::from([board.to_vec()]);
+    let mut queue = vec![board.to_vec()];
+    while let Some(next) = queue.pop() {
+        if goal.contains(&next) {
+            return true;
+        }
+        for option in rules.rewrites(&next) {
+            if seen.insert(option.clone()) {
+                queue.push(option);
+            }
+        }
+    }
+    false
+}
+
 fn assert_k<S>(solver: &S, k: usize, test_string: &str)
 where
     S: SRSSolver,